@@ -1,42 +1,139 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::{self, Approve, Mint, MintTo, Revoke, Token, TokenAccount, Transfer},
+};
+use mpl_token_metadata::{
+    instruction::{
+        burn_nft, create_master_edition_v3, create_metadata_accounts_v3,
+        freeze_delegated_account, set_and_verify_sized_collection_item, sign_metadata,
+        thaw_delegated_account, unverify_sized_collection_item, update_metadata_accounts_v2,
+    },
+    pda::{find_master_edition_account, find_metadata_account},
+    state::{
+        CollectionDetails, Creator, DataV2, Metadata, TokenMetadataAccount, MAX_CREATOR_LIMIT,
+        MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+    },
 };
-use mpl_token_metadata::state::{Collection, Creator, DataV2, Metadata};
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+/// The memory_validator program, whose config PDA is the only signer allowed
+/// to write validator consensus back into `Memory.quality`
+pub const MEMORY_VALIDATOR_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("J3dSk82HQaUzS7bzEp7neYtwEv6V8xzjEwLG1WYM8fmH");
+
 #[program]
 pub mod memory_nft {
     use super::*;
 
+    #[allow(clippy::result_large_err)]
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let memory_data = &mut ctx.accounts.memory_data;
         memory_data.authority = ctx.accounts.authority.key();
         memory_data.memory_count = 0;
         memory_data.bump = *ctx.bumps.get("memory_data").unwrap();
         
+        emit!(MemoryProgramInitialized {
+            memory_data: memory_data.key(),
+            authority: memory_data.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("MemoryNFT program initialized");
         Ok(())
     }
 
+    #[allow(clippy::result_large_err, clippy::too_many_arguments)]
     pub fn mint_memory(
         ctx: Context<MintMemory>,
         uri: String,
         name: String,
         symbol: String,
-        memory_type: String,
+        memory_type: MemoryType,
         memory_quality: u8,
-        neural_fingerprint: String,
-        timestamp: i64,
+        neural_fingerprint: [u8; 32],
+        captured_at: i64,
+        soulbound: bool,
+        seller_fee_basis_points: u16,
+        creators: Vec<MemoryCreator>,
     ) -> Result<()> {
         // Validate inputs
         require!(!uri.is_empty(), ErrorCode::EmptyUri);
         require!(!name.is_empty(), ErrorCode::EmptyName);
         require!(!symbol.is_empty(), ErrorCode::EmptySymbol);
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+        require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::NameTooLong);
+        require!(symbol.len() <= MAX_SYMBOL_LENGTH, ErrorCode::SymbolTooLong);
         require!(memory_quality > 0 && memory_quality <= 4, ErrorCode::InvalidQuality);
-        require!(!neural_fingerprint.is_empty(), ErrorCode::EmptyFingerprint);
+        require!(neural_fingerprint != [0; 32], ErrorCode::EmptyFingerprint);
+        require!(seller_fee_basis_points <= 10_000, ErrorCode::InvalidRoyalty);
+        
+        // The minting authority is always a creator, and the only creator by default
+        let authority_key = ctx.accounts.authority.key();
+        let creators = if creators.is_empty() {
+            vec![MemoryCreator { address: authority_key, share: 100 }]
+        } else {
+            creators
+        };
+        require!(creators.len() <= MAX_CREATOR_LIMIT, ErrorCode::TooManyCreators);
+        require!(
+            creators.iter().any(|creator| creator.address == authority_key),
+            ErrorCode::AuthorityNotCreator
+        );
+        require!(
+            creators.iter().map(|creator| creator.share as u16).sum::<u16>() == 100,
+            ErrorCode::InvalidCreatorShares
+        );
+        let creators = creators
+            .iter()
+            .map(|creator| Creator {
+                address: creator.address,
+                verified: false,
+                share: creator.share,
+            })
+            .collect();
+        
+        // Each neural fingerprint can back only one live memory
+        require!(
+            ctx.accounts.fingerprint_record.mint == Pubkey::default(),
+            ErrorCode::DuplicateFingerprint
+        );
+        
+        // Mint the single token backing this memory, with its metadata and master edition
+        mint_master_edition_nft(
+            &ctx.accounts.memory_data,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.token_account.to_account_info(),
+            &ctx.accounts.metadata.to_account_info(),
+            &ctx.accounts.master_edition.to_account_info(),
+            &ctx.accounts.token_metadata_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.rent.to_account_info(),
+            name.clone(),
+            symbol,
+            uri,
+            Some(creators),
+            seller_fee_basis_points,
+            None,
+        )?;
+        
+        // Verify the minting authority as a creator so marketplaces honour the royalty
+        invoke(
+            &sign_metadata(
+                ctx.accounts.token_metadata_program.key(),
+                ctx.accounts.metadata.key(),
+                authority_key,
+            ),
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+        )?;
         
         // Update memory count
         let memory_data = &mut ctx.accounts.memory_data;
@@ -49,37 +146,751 @@ pub mod memory_nft {
         memory.memory_type = memory_type;
         memory.quality = memory_quality;
         memory.neural_fingerprint = neural_fingerprint;
-        memory.created_at = timestamp;
-        memory.is_transferable = true;
+        memory.captured_at = captured_at;
+        memory.created_at = Clock::get()?.unix_timestamp;
+        memory.is_transferable = !soulbound;
+        memory.is_soulbound = soulbound;
         memory.memory_data = memory_data.key();
+        memory.collection = None;
         memory.bump = *ctx.bumps.get("memory").unwrap();
         
+        // Register the fingerprint so it maps back to this mint
+        let fingerprint_record = &mut ctx.accounts.fingerprint_record;
+        fingerprint_record.neural_fingerprint = neural_fingerprint;
+        fingerprint_record.mint = memory.mint;
+        fingerprint_record.memory = memory.key();
+        fingerprint_record.bump = *ctx.bumps.get("fingerprint_record").unwrap();
+        
+        // Soulbound memories are frozen in the minter's wallet from the start
+        if soulbound {
+            freeze_memory_token(
+                &ctx.accounts.memory,
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.token_account.to_account_info(),
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.master_edition.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.token_metadata_program.to_account_info(),
+            )?;
+        }
+        
+        emit!(MemoryMinted {
+            memory: ctx.accounts.memory.key(),
+            mint: ctx.accounts.mint.key(),
+            owner: ctx.accounts.authority.key(),
+            memory_type,
+            quality: memory_quality,
+            is_soulbound: soulbound,
+            seller_fee_basis_points,
+            timestamp: ctx.accounts.memory.created_at,
+        });
+        
         msg!("Memory NFT minted: {}", name);
         Ok(())
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn transfer_memory(ctx: Context<TransferMemory>) -> Result<()> {
-        let memory = &mut ctx.accounts.memory;
-        
         // Ensure memory is transferable
-        require!(memory.is_transferable, ErrorCode::NonTransferable);
+        require!(ctx.accounts.memory.is_transferable, ErrorCode::NonTransferable);
+        
+        // Move the token to the new owner's token account
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.from_token_account.to_account_info(),
+                    to: ctx.accounts.to_token_account.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            1,
+        )?;
         
         // Update memory owner
+        let memory = &mut ctx.accounts.memory;
+        let previous_owner = memory.owner;
         memory.owner = ctx.accounts.new_owner.key();
         
+        emit!(MemoryTransferred {
+            memory: memory.key(),
+            mint: memory.mint,
+            from: previous_owner,
+            to: memory.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Memory NFT transferred to: {}", memory.owner);
         Ok(())
     }
 
+    #[allow(clippy::result_large_err)]
+    pub fn sync_owner(ctx: Context<SyncOwner>) -> Result<()> {
+        // Re-sync the recorded owner with whoever holds the token, e.g. after
+        // a wallet or marketplace transfer that did not go through this program
+        let memory = &mut ctx.accounts.memory;
+        let previous_owner = memory.owner;
+        memory.owner = ctx.accounts.token_account.owner;
+        
+        if previous_owner != memory.owner {
+            emit!(MemoryTransferred {
+                memory: memory.key(),
+                mint: memory.mint,
+                from: previous_owner,
+                to: memory.owner,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        
+        msg!("Memory NFT owner synced to: {}", memory.owner);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn burn_memory<'info>(ctx: Context<'_, '_, '_, 'info, BurnMemory<'info>>) -> Result<()> {
+        // Frozen tokens cannot be burned, so thaw non-transferable memories first
+        if !ctx.accounts.memory.is_transferable {
+            thaw_memory_token(
+                &ctx.accounts.memory,
+                &ctx.accounts.token_account.to_account_info(),
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.master_edition.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.token_metadata_program.to_account_info(),
+            )?;
+        }
+        
+        // Memories in a collection pass the collection metadata and the
+        // collection account as remaining accounts, so both sizes stay correct
+        let collection_metadata = match ctx.accounts.memory.collection {
+            Some(collection_mint) => {
+                let collection_metadata = ctx
+                    .remaining_accounts
+                    .first()
+                    .ok_or(ErrorCode::MissingCollectionAccounts)?;
+                let memory_collection_info = ctx
+                    .remaining_accounts
+                    .get(1)
+                    .ok_or(ErrorCode::MissingCollectionAccounts)?;
+                require_keys_eq!(
+                    collection_metadata.key(),
+                    find_metadata_account(&collection_mint).0,
+                    ErrorCode::InvalidCollectionAccount
+                );
+                
+                let mut memory_collection: Account<MemoryCollection> =
+                    Account::try_from(memory_collection_info)?;
+                require_keys_eq!(
+                    memory_collection.collection_mint,
+                    collection_mint,
+                    ErrorCode::InvalidCollectionAccount
+                );
+                memory_collection.item_count = memory_collection.item_count.checked_sub(1).unwrap();
+                memory_collection.exit(&crate::ID)?;
+                
+                Some(collection_metadata.clone())
+            }
+            None => None,
+        };
+        
+        // Burn the token and close the token, metadata and edition accounts,
+        // refunding their rent to the owner
+        let mut burn_accounts = vec![
+            ctx.accounts.metadata.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.master_edition.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.token_metadata_program.to_account_info(),
+        ];
+        if let Some(collection_metadata) = &collection_metadata {
+            burn_accounts.push(collection_metadata.clone());
+        }
+        invoke(
+            &burn_nft(
+                ctx.accounts.token_metadata_program.key(),
+                ctx.accounts.metadata.key(),
+                ctx.accounts.owner.key(),
+                ctx.accounts.mint.key(),
+                ctx.accounts.token_account.key(),
+                ctx.accounts.master_edition.key(),
+                ctx.accounts.token_program.key(),
+                collection_metadata.map(|collection_metadata| collection_metadata.key()),
+            ),
+            &burn_accounts,
+        )?;
+        
+        // Update memory count
+        let memory_data = &mut ctx.accounts.memory_data;
+        memory_data.memory_count = memory_data.memory_count.checked_sub(1).unwrap();
+        
+        // The metadata history only exists once the memory has been updated
+        let memory_history = &ctx.accounts.memory_history;
+        if memory_history.owner == ctx.program_id {
+            Account::<MemoryHistory>::try_from(memory_history)?
+                .close(ctx.accounts.owner.to_account_info())?;
+        }
+        
+        // The memory account itself is closed to the owner by the `close` constraint
+        emit!(MemoryBurned {
+            memory: ctx.accounts.memory.key(),
+            mint: ctx.accounts.mint.key(),
+            owner: ctx.accounts.owner.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Memory NFT burned: {}", ctx.accounts.mint.key());
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn update_memory_metadata(
+        ctx: Context<UpdateMemoryMetadata>,
+        uri: String,
+        name: Option<String>,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        // The current holder or the program's update authority can change the
+        // metadata. The cached owner goes stale on plain SPL transfers, so check
+        // who actually holds the token.
+        let authority = ctx.accounts.authority.key();
+        let token_account = &ctx.accounts.token_account;
+        require!(
+            (token_account.owner == authority && token_account.amount == 1)
+                || authority == ctx.accounts.memory_data.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        // Validate inputs
+        require!(!uri.is_empty(), ErrorCode::EmptyUri);
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+        if let Some(name) = &name {
+            require!(!name.is_empty(), ErrorCode::EmptyName);
+            require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::NameTooLong);
+        }
+        require!(content_hash != [0u8; 32], ErrorCode::EmptyContentHash);
+        
+        // Keep everything but the name and URI as it is on chain. Metaplex pads
+        // strings with null bytes, so trim them before writing them back.
+        let metadata = Metadata::from_account_info(&ctx.accounts.metadata.to_account_info())?;
+        let previous_uri = metadata.data.uri.trim_end_matches('\0').to_string();
+        let data = DataV2 {
+            name: name.unwrap_or_else(|| metadata.data.name.trim_end_matches('\0').to_string()),
+            symbol: metadata.data.symbol.trim_end_matches('\0').to_string(),
+            uri: uri.clone(),
+            seller_fee_basis_points: metadata.data.seller_fee_basis_points,
+            creators: metadata.data.creators,
+            collection: metadata.collection,
+            uses: metadata.uses,
+        };
+        
+        let memory_data_seeds: &[&[u8]] = &[b"memory-data", &[ctx.accounts.memory_data.bump]];
+        invoke_signed(
+            &update_metadata_accounts_v2(
+                ctx.accounts.token_metadata_program.key(),
+                ctx.accounts.metadata.key(),
+                ctx.accounts.memory_data.key(),
+                None,
+                Some(data),
+                None,
+                None,
+            ),
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.memory_data.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            &[memory_data_seeds],
+        )?;
+        
+        // Record the URI being replaced, dropping the oldest entry once the
+        // history is full
+        let timestamp = Clock::get()?.unix_timestamp;
+        let memory_history = &mut ctx.accounts.memory_history;
+        if memory_history.memory == Pubkey::default() {
+            memory_history.memory = ctx.accounts.memory.key();
+            memory_history.bump = *ctx.bumps.get("memory_history").unwrap();
+        }
+        if memory_history.entries.len() == MemoryHistory::MAX_ENTRIES {
+            memory_history.entries.remove(0);
+        }
+        let previous_content_hash = memory_history.content_hash;
+        memory_history.entries.push(UriRecord {
+            uri: previous_uri,
+            content_hash: previous_content_hash,
+            replaced_at: timestamp,
+        });
+        memory_history.content_hash = content_hash;
+        memory_history.version = memory_history.version.checked_add(1).unwrap();
+        
+        emit!(MemoryMetadataUpdated {
+            memory: ctx.accounts.memory.key(),
+            mint: ctx.accounts.memory.mint,
+            authority,
+            uri,
+            content_hash,
+            version: memory_history.version,
+            timestamp,
+        });
+        
+        msg!("Memory metadata updated to version {}", memory_history.version);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn set_memory_quality(ctx: Context<SetMemoryQuality>, quality: u8) -> Result<()> {
+        require!(quality > 0 && quality <= 4, ErrorCode::InvalidQuality);
+        
+        let memory = &mut ctx.accounts.memory;
+        let previous_quality = memory.quality;
+        memory.quality = quality;
+        
+        emit!(MemoryQualityUpdated {
+            memory: memory.key(),
+            mint: memory.mint,
+            previous_quality,
+            quality,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Memory quality set by validator consensus: {}", quality);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn create_collection(
+        ctx: Context<CreateCollection>,
+        name: String,
+        symbol: String,
+        uri: String,
+        memory_type: Option<MemoryType>,
+    ) -> Result<()> {
+        // Validate inputs
+        require!(!uri.is_empty(), ErrorCode::EmptyUri);
+        require!(!name.is_empty(), ErrorCode::EmptyName);
+        require!(!symbol.is_empty(), ErrorCode::EmptySymbol);
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+        require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::NameTooLong);
+        require!(symbol.len() <= MAX_SYMBOL_LENGTH, ErrorCode::SymbolTooLong);
+        
+        // Platform-wide collections per memory type are reserved for the program authority
+        if memory_type.is_some() {
+            require_keys_eq!(
+                ctx.accounts.curator.key(),
+                ctx.accounts.memory_data.authority,
+                ErrorCode::NotAuthorized
+            );
+        }
+        
+        // Sized collections are deprecated upstream but still the only way
+        // to verify items through the v1 instruction set.
+        #[allow(deprecated)]
+        let collection_details = CollectionDetails::V1 { size: 0 };
+        
+        // Mint the collection NFT as a sized Metaplex collection
+        mint_master_edition_nft(
+            &ctx.accounts.memory_data,
+            &ctx.accounts.curator.to_account_info(),
+            &ctx.accounts.collection_mint.to_account_info(),
+            &ctx.accounts.collection_token_account.to_account_info(),
+            &ctx.accounts.collection_metadata.to_account_info(),
+            &ctx.accounts.collection_master_edition.to_account_info(),
+            &ctx.accounts.token_metadata_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.rent.to_account_info(),
+            name.clone(),
+            symbol,
+            uri,
+            None,
+            0,
+            Some(collection_details),
+        )?;
+        
+        let memory_collection = &mut ctx.accounts.memory_collection;
+        memory_collection.collection_mint = ctx.accounts.collection_mint.key();
+        memory_collection.curator = ctx.accounts.curator.key();
+        memory_collection.memory_type = memory_type;
+        memory_collection.item_count = 0;
+        memory_collection.created_at = Clock::get()?.unix_timestamp;
+        memory_collection.bump = *ctx.bumps.get("memory_collection").unwrap();
+        
+        emit!(MemoryCollectionCreated {
+            collection: memory_collection.key(),
+            collection_mint: memory_collection.collection_mint,
+            curator: memory_collection.curator,
+            memory_type,
+            timestamp: memory_collection.created_at,
+        });
+        
+        msg!("Memory collection created: {}", name);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn add_to_collection(ctx: Context<UpdateCollectionItem>) -> Result<()> {
+        let memory = &ctx.accounts.memory;
+        let memory_collection = &ctx.accounts.memory_collection;
+        
+        // Only the curator adds items
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            memory_collection.curator,
+            ErrorCode::NotCollectionCurator
+        );
+        require!(memory.collection.is_none(), ErrorCode::AlreadyInCollection);
+        
+        // Platform collections take any memory of their type, creator
+        // collections only memories the curator holds
+        match memory_collection.memory_type {
+            Some(memory_type) => {
+                require!(memory.memory_type == memory_type, ErrorCode::MemoryTypeMismatch)
+            }
+            None => require_keys_eq!(
+                ctx.accounts.token_account.owner,
+                memory_collection.curator,
+                ErrorCode::NotMemoryOwner
+            ),
+        }
+        
+        // Set and verify the collection on the memory's metadata. The memory
+        // data PDA is both the item's update authority and the collection authority.
+        let memory_data_seeds: &[&[u8]] = &[b"memory-data", &[ctx.accounts.memory_data.bump]];
+        invoke_signed(
+            &set_and_verify_sized_collection_item(
+                ctx.accounts.token_metadata_program.key(),
+                ctx.accounts.metadata.key(),
+                ctx.accounts.memory_data.key(),
+                ctx.accounts.authority.key(),
+                ctx.accounts.memory_data.key(),
+                ctx.accounts.collection_mint.key(),
+                ctx.accounts.collection_metadata.key(),
+                ctx.accounts.collection_master_edition.key(),
+                None,
+            ),
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.memory_data.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.collection_mint.to_account_info(),
+                ctx.accounts.collection_metadata.to_account_info(),
+                ctx.accounts.collection_master_edition.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            &[memory_data_seeds],
+        )?;
+        
+        let memory = &mut ctx.accounts.memory;
+        memory.collection = Some(ctx.accounts.collection_mint.key());
+        
+        let memory_collection = &mut ctx.accounts.memory_collection;
+        memory_collection.item_count = memory_collection.item_count.checked_add(1).unwrap();
+        
+        emit!(MemoryCollectionUpdated {
+            collection: memory_collection.key(),
+            memory: memory.key(),
+            mint: memory.mint,
+            added: true,
+            item_count: memory_collection.item_count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Memory added to collection: {}", memory_collection.collection_mint);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn remove_from_collection(ctx: Context<UpdateCollectionItem>) -> Result<()> {
+        // The curator or the memory's holder can take a memory out of a collection
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == ctx.accounts.memory_collection.curator
+                || authority == ctx.accounts.token_account.owner,
+            ErrorCode::NotCollectionCurator
+        );
+        require!(
+            ctx.accounts.memory.collection == Some(ctx.accounts.collection_mint.key()),
+            ErrorCode::NotInCollection
+        );
+        
+        let memory_data_seeds: &[&[u8]] = &[b"memory-data", &[ctx.accounts.memory_data.bump]];
+        invoke_signed(
+            &unverify_sized_collection_item(
+                ctx.accounts.token_metadata_program.key(),
+                ctx.accounts.metadata.key(),
+                ctx.accounts.memory_data.key(),
+                ctx.accounts.authority.key(),
+                ctx.accounts.collection_mint.key(),
+                ctx.accounts.collection_metadata.key(),
+                ctx.accounts.collection_master_edition.key(),
+                None,
+            ),
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.memory_data.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.collection_mint.to_account_info(),
+                ctx.accounts.collection_metadata.to_account_info(),
+                ctx.accounts.collection_master_edition.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            &[memory_data_seeds],
+        )?;
+        
+        let memory = &mut ctx.accounts.memory;
+        memory.collection = None;
+        
+        let memory_collection = &mut ctx.accounts.memory_collection;
+        memory_collection.item_count = memory_collection.item_count.checked_sub(1).unwrap();
+        
+        emit!(MemoryCollectionUpdated {
+            collection: memory_collection.key(),
+            memory: memory.key(),
+            mint: memory.mint,
+            added: false,
+            item_count: memory_collection.item_count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Memory removed from collection: {}", memory_collection.collection_mint);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
     pub fn toggle_transferable(ctx: Context<ToggleTransferable>, is_transferable: bool) -> Result<()> {
+        // Soulbound memories can never be made transferable
+        require!(
+            !(ctx.accounts.memory.is_soulbound && is_transferable),
+            ErrorCode::Soulbound
+        );
+        
+        // Freeze or thaw the holder's token account to enforce the flag
+        if ctx.accounts.memory.is_transferable != is_transferable {
+            if is_transferable {
+                thaw_memory_token(
+                    &ctx.accounts.memory,
+                    &ctx.accounts.token_account.to_account_info(),
+                    &ctx.accounts.mint.to_account_info(),
+                    &ctx.accounts.master_edition.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    &ctx.accounts.token_metadata_program.to_account_info(),
+                )?;
+                
+                token::revoke(CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Revoke {
+                        source: ctx.accounts.token_account.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ))?;
+            } else {
+                freeze_memory_token(
+                    &ctx.accounts.memory,
+                    &ctx.accounts.authority.to_account_info(),
+                    &ctx.accounts.token_account.to_account_info(),
+                    &ctx.accounts.mint.to_account_info(),
+                    &ctx.accounts.master_edition.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    &ctx.accounts.token_metadata_program.to_account_info(),
+                )?;
+            }
+        }
+        
         let memory = &mut ctx.accounts.memory;
         memory.is_transferable = is_transferable;
         
+        emit!(TransferabilityChanged {
+            memory: memory.key(),
+            mint: memory.mint,
+            owner: memory.owner,
+            is_transferable,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Memory transferability set to: {}", is_transferable);
         Ok(())
     }
 }
 
+/// Mints a single token to `token_account` and creates its Metaplex metadata
+/// and a master edition with a max supply of 0, so the NFT stays a 1/1. The
+/// memory data PDA is the update authority, and the master edition takes over
+/// the mint and freeze authorities.
+#[allow(clippy::result_large_err, clippy::too_many_arguments)]
+fn mint_master_edition_nft<'info>(
+    memory_data: &Account<'info, MemoryData>,
+    authority: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    metadata: &AccountInfo<'info>,
+    master_edition: &AccountInfo<'info>,
+    token_metadata_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rent: &AccountInfo<'info>,
+    name: String,
+    symbol: String,
+    uri: String,
+    creators: Option<Vec<Creator>>,
+    seller_fee_basis_points: u16,
+    collection_details: Option<CollectionDetails>,
+) -> Result<()> {
+    token::mint_to(
+        CpiContext::new(
+            token_program.clone(),
+            MintTo {
+                mint: mint.clone(),
+                to: token_account.clone(),
+                authority: authority.clone(),
+            },
+        ),
+        1,
+    )?;
+    
+    let memory_data_seeds: &[&[u8]] = &[b"memory-data", &[memory_data.bump]];
+    
+    invoke_signed(
+        &create_metadata_accounts_v3(
+            token_metadata_program.key(),
+            metadata.key(),
+            mint.key(),
+            authority.key(),
+            authority.key(),
+            memory_data.key(),
+            name,
+            symbol,
+            uri,
+            creators,
+            seller_fee_basis_points,
+            true,
+            true,
+            None,
+            None,
+            collection_details,
+        ),
+        &[
+            metadata.clone(),
+            mint.clone(),
+            authority.clone(),
+            memory_data.to_account_info(),
+            system_program.clone(),
+            rent.clone(),
+            token_metadata_program.clone(),
+        ],
+        &[memory_data_seeds],
+    )?;
+    
+    invoke_signed(
+        &create_master_edition_v3(
+            token_metadata_program.key(),
+            master_edition.key(),
+            mint.key(),
+            memory_data.key(),
+            authority.key(),
+            metadata.key(),
+            authority.key(),
+            Some(0),
+        ),
+        &[
+            master_edition.clone(),
+            mint.clone(),
+            memory_data.to_account_info(),
+            authority.clone(),
+            metadata.clone(),
+            token_program.clone(),
+            system_program.clone(),
+            rent.clone(),
+            token_metadata_program.clone(),
+        ],
+        &[memory_data_seeds],
+    )?;
+    
+    Ok(())
+}
+
+/// Approves the memory PDA as delegate of the holder's token account and
+/// freezes it through the token metadata program, which holds the mint's
+/// freeze authority via the master edition.
+#[allow(clippy::result_large_err)]
+fn freeze_memory_token<'info>(
+    memory: &Account<'info, Memory>,
+    holder: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    master_edition: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    token_metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    token::approve(
+        CpiContext::new(
+            token_program.clone(),
+            Approve {
+                to: token_account.clone(),
+                delegate: memory.to_account_info(),
+                authority: holder.clone(),
+            },
+        ),
+        1,
+    )?;
+    
+    let memory_seeds: &[&[u8]] = &[b"memory", memory.mint.as_ref(), &[memory.bump]];
+    invoke_signed(
+        &freeze_delegated_account(
+            token_metadata_program.key(),
+            memory.key(),
+            token_account.key(),
+            master_edition.key(),
+            mint.key(),
+        ),
+        &[
+            memory.to_account_info(),
+            token_account.clone(),
+            master_edition.clone(),
+            mint.clone(),
+            token_program.clone(),
+            token_metadata_program.clone(),
+        ],
+        &[memory_seeds],
+    )?;
+    
+    Ok(())
+}
+
+/// Thaws a token account previously frozen by `freeze_memory_token`. The
+/// memory PDA stays the delegate until the holder revokes it.
+#[allow(clippy::result_large_err)]
+fn thaw_memory_token<'info>(
+    memory: &Account<'info, Memory>,
+    token_account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    master_edition: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    token_metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let memory_seeds: &[&[u8]] = &[b"memory", memory.mint.as_ref(), &[memory.bump]];
+    invoke_signed(
+        &thaw_delegated_account(
+            token_metadata_program.key(),
+            memory.key(),
+            token_account.key(),
+            master_edition.key(),
+            mint.key(),
+        ),
+        &[
+            memory.to_account_info(),
+            token_account.clone(),
+            master_edition.clone(),
+            mint.clone(),
+            token_program.clone(),
+            token_metadata_program.clone(),
+        ],
+        &[memory_seeds],
+    )?;
+    
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(mut)]
@@ -98,6 +909,14 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(
+    uri: String,
+    name: String,
+    symbol: String,
+    memory_type: MemoryType,
+    memory_quality: u8,
+    neural_fingerprint: [u8; 32],
+)]
 pub struct MintMemory<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -118,11 +937,21 @@ pub struct MintMemory<'info> {
     )]
     pub memory: Account<'info, Memory>,
     
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + FingerprintRecord::LEN,
+        seeds = [b"fingerprint", neural_fingerprint.as_ref()],
+        bump
+    )]
+    pub fingerprint_record: Account<'info, FingerprintRecord>,
+    
     #[account(
         init,
         payer = authority,
         mint::decimals = 0,
         mint::authority = authority,
+        mint::freeze_authority = authority,
     )]
     pub mint: Account<'info, Mint>,
     
@@ -134,6 +963,24 @@ pub struct MintMemory<'info> {
     )]
     pub token_account: Account<'info, TokenAccount>,
     
+    /// CHECK: Created by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_metadata_account(&mint.key()).0
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Created by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_master_edition_account(&mint.key()).0
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+    
+    /// CHECK: This is the Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -162,6 +1009,7 @@ pub struct TransferMemory<'info> {
         mut,
         associated_token::mint = mint,
         associated_token::authority = authority,
+        constraint = from_token_account.amount == 1 @ ErrorCode::TokenNotHeld
     )]
     pub from_token_account: Account<'info, TokenAccount>,
     
@@ -179,6 +1027,259 @@ pub struct TransferMemory<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct BurnMemory<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory-data"],
+        bump = memory_data.bump
+    )]
+    pub memory_data: Account<'info, MemoryData>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", mint.key().as_ref()],
+        bump = memory.bump,
+        constraint = memory.owner == owner.key() @ ErrorCode::NotMemoryOwner,
+        close = owner
+    )]
+    pub memory: Account<'info, Memory>,
+    
+    #[account(
+        mut,
+        seeds = [b"fingerprint", fingerprint_record.neural_fingerprint.as_ref()],
+        bump = fingerprint_record.bump,
+        constraint = fingerprint_record.mint == mint.key() @ ErrorCode::InvalidFingerprintRecord,
+        close = owner
+    )]
+    pub fingerprint_record: Account<'info, FingerprintRecord>,
+    
+    /// CHECK: Memory history PDA, closed to the owner if it was ever created
+    #[account(
+        mut,
+        seeds = [b"memory-history", memory.key().as_ref()],
+        bump
+    )]
+    pub memory_history: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        constraint = token_account.amount == 1 @ ErrorCode::TokenNotHeld
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Closed by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_metadata_account(&mint.key()).0
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Closed by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_master_edition_account(&mint.key()).0
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+    
+    /// CHECK: This is the Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMemoryMetadata<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"memory-data"],
+        bump = memory_data.bump
+    )]
+    pub memory_data: Account<'info, MemoryData>,
+    
+    #[account(
+        seeds = [b"memory", memory.mint.as_ref()],
+        bump = memory.bump
+    )]
+    pub memory: Account<'info, Memory>,
+    
+    /// Any token account of the memory's mint; the holder must pass the one
+    /// holding the token, the update authority can pass the holder's
+    #[account(constraint = token_account.mint == memory.mint @ ErrorCode::TokenNotHeld)]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MemoryHistory::LEN,
+        seeds = [b"memory-history", memory.key().as_ref()],
+        bump
+    )]
+    pub memory_history: Account<'info, MemoryHistory>,
+    
+    /// CHECK: Updated by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_metadata_account(&memory.mint).0
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: This is the Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMemoryQuality<'info> {
+    /// The memory_validator config PDA, signing through CPI
+    #[account(
+        seeds = [b"validator-config"],
+        bump,
+        seeds::program = MEMORY_VALIDATOR_PROGRAM_ID
+    )]
+    pub validator_authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", memory.mint.as_ref()],
+        bump = memory.bump
+    )]
+    pub memory: Account<'info, Memory>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCollection<'info> {
+    #[account(mut)]
+    pub curator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"memory-data"],
+        bump = memory_data.bump
+    )]
+    pub memory_data: Account<'info, MemoryData>,
+    
+    #[account(
+        init,
+        payer = curator,
+        space = 8 + MemoryCollection::LEN,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub memory_collection: Account<'info, MemoryCollection>,
+    
+    #[account(
+        init,
+        payer = curator,
+        mint::decimals = 0,
+        mint::authority = curator,
+        mint::freeze_authority = curator,
+    )]
+    pub collection_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = curator,
+        associated_token::mint = collection_mint,
+        associated_token::authority = curator,
+    )]
+    pub collection_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Created by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_metadata_account(&collection_mint.key()).0
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Created by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_master_edition_account(&collection_mint.key()).0
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+    
+    /// CHECK: This is the Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollectionItem<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"memory-data"],
+        bump = memory_data.bump
+    )]
+    pub memory_data: Account<'info, MemoryData>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", mint.key().as_ref()],
+        bump = memory.bump
+    )]
+    pub memory: Account<'info, Memory>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    /// The token account holding the memory NFT
+    #[account(
+        constraint = token_account.mint == mint.key() @ ErrorCode::TokenNotHeld,
+        constraint = token_account.amount == 1 @ ErrorCode::TokenNotHeld
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Updated by the token metadata program, address checked against the mint
+    #[account(
+        mut,
+        address = find_metadata_account(&mint.key()).0
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump = memory_collection.bump
+    )]
+    pub memory_collection: Account<'info, MemoryCollection>,
+    
+    pub collection_mint: Account<'info, Mint>,
+    
+    /// CHECK: Updated by the token metadata program, address checked against the collection mint
+    #[account(
+        mut,
+        address = find_metadata_account(&collection_mint.key()).0
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Address checked against the collection mint
+    #[account(address = find_master_edition_account(&collection_mint.key()).0)]
+    pub collection_master_edition: UncheckedAccount<'info>,
+    
+    /// CHECK: This is the Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ToggleTransferable<'info> {
     #[account(mut)]
@@ -193,6 +1294,42 @@ pub struct ToggleTransferable<'info> {
     pub memory: Account<'info, Memory>,
     
     pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+        constraint = token_account.amount == 1 @ ErrorCode::TokenNotHeld
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Freeze authority of the mint, address checked against the mint
+    #[account(address = find_master_edition_account(&mint.key()).0)]
+    pub master_edition: UncheckedAccount<'info>,
+    
+    /// CHECK: This is the Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SyncOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"memory", mint.key().as_ref()],
+        bump = memory.bump
+    )]
+    pub memory: Account<'info, Memory>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        constraint = token_account.mint == mint.key() @ ErrorCode::TokenNotHeld,
+        constraint = token_account.amount == 1 @ ErrorCode::TokenNotHeld
+    )]
+    pub token_account: Account<'info, TokenAccount>,
 }
 
 #[account]
@@ -210,17 +1347,185 @@ impl MemoryData {
 pub struct Memory {
     pub owner: Pubkey,
     pub mint: Pubkey,
-    pub memory_type: String,
+    pub memory_type: MemoryType,
     pub quality: u8,
-    pub neural_fingerprint: String,
+    /// SHA-256 digest of the captured neural data
+    pub neural_fingerprint: [u8; 32],
+    /// Client-reported time the memory was captured, not verified on-chain
+    pub captured_at: i64,
+    /// On-chain time the memory was minted
     pub created_at: i64,
     pub is_transferable: bool,
+    pub is_soulbound: bool,
     pub memory_data: Pubkey,
+    /// Mint of the verified collection this memory belongs to, if any
+    pub collection: Option<Pubkey>,
     pub bump: u8,
 }
 
 impl Memory {
-    pub const LEN: usize = 32 + 32 + 64 + 1 + 128 + 8 + 1 + 32 + 1;
+    pub const LEN: usize = 32 + 32 + 1 + 1 + 32 + 8 + 8 + 1 + 1 + 32 + 33 + 1;
+}
+
+/// Bounded audit trail of the URIs a memory pointed to before its current one
+#[account]
+pub struct MemoryHistory {
+    pub memory: Pubkey,
+    /// Content hash of the current URI, zero until the first update
+    pub content_hash: [u8; 32],
+    /// Number of metadata updates applied so far
+    pub version: u32,
+    /// Replaced URIs, oldest first
+    pub entries: Vec<UriRecord>,
+    pub bump: u8,
+}
+
+impl MemoryHistory {
+    pub const MAX_ENTRIES: usize = 8;
+    pub const LEN: usize = 32 + 32 + 4 + 4 + Self::MAX_ENTRIES * UriRecord::LEN + 1;
+}
+
+/// A URI a memory used to point to, with the hash of the content it served
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UriRecord {
+    pub uri: String,
+    pub content_hash: [u8; 32],
+    pub replaced_at: i64,
+}
+
+impl UriRecord {
+    pub const LEN: usize = 4 + MAX_URI_LENGTH + 32 + 8;
+}
+
+/// A verified Metaplex collection of memories. Collections with a memory type
+/// are platform-wide and curated by the program authority; collections without
+/// one belong to the creator who curates them.
+#[account]
+pub struct MemoryCollection {
+    pub collection_mint: Pubkey,
+    pub curator: Pubkey,
+    pub memory_type: Option<MemoryType>,
+    pub item_count: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl MemoryCollection {
+    pub const LEN: usize = 32 + 32 + 2 + 8 + 8 + 1;
+}
+
+/// A royalty recipient recorded in the memory's Metaplex metadata
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct MemoryCreator {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+/// Memory categories, matching the app's `MemoryType`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryType {
+    Cognitive,
+    Emotional,
+    Cultural,
+    Therapeutic,
+}
+
+/// Maps a neural fingerprint to the memory minted from it. Clients look up a
+/// fingerprint by deriving `[b"fingerprint", neural_fingerprint]`.
+#[account]
+pub struct FingerprintRecord {
+    pub neural_fingerprint: [u8; 32],
+    pub mint: Pubkey,
+    pub memory: Pubkey,
+    pub bump: u8,
+}
+
+impl FingerprintRecord {
+    pub const LEN: usize = 32 + 32 + 32 + 1;
+}
+
+#[event]
+pub struct MemoryProgramInitialized {
+    pub memory_data: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryMinted {
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub memory_type: MemoryType,
+    pub quality: u8,
+    pub is_soulbound: bool,
+    pub seller_fee_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryTransferred {
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferabilityChanged {
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub is_transferable: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryMetadataUpdated {
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryQualityUpdated {
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub previous_quality: u8,
+    pub quality: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryCollectionCreated {
+    pub collection: Pubkey,
+    pub collection_mint: Pubkey,
+    pub curator: Pubkey,
+    pub memory_type: Option<MemoryType>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryCollectionUpdated {
+    pub collection: Pubkey,
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub added: bool,
+    pub item_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryBurned {
+    pub memory: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
 }
 
 #[error_code]
@@ -234,15 +1539,147 @@ pub enum ErrorCode {
     #[msg("Symbol cannot be empty")]
     EmptySymbol,
     
+    #[msg("URI is too long")]
+    UriTooLong,
+    
+    #[msg("Name is too long")]
+    NameTooLong,
+    
+    #[msg("Symbol is too long")]
+    SymbolTooLong,
+    
     #[msg("Invalid memory quality (must be 1-4)")]
     InvalidQuality,
     
+    #[msg("Royalty cannot exceed 10000 basis points")]
+    InvalidRoyalty,
+    
+    #[msg("Too many creators")]
+    TooManyCreators,
+    
+    #[msg("Creator shares must add up to 100")]
+    InvalidCreatorShares,
+    
+    #[msg("The minting authority must be one of the creators")]
+    AuthorityNotCreator,
+    
     #[msg("Neural fingerprint cannot be empty")]
     EmptyFingerprint,
     
+    #[msg("A memory with this neural fingerprint has already been minted")]
+    DuplicateFingerprint,
+    
     #[msg("Not the owner of this memory NFT")]
     NotMemoryOwner,
     
     #[msg("This memory NFT is not transferable")]
     NonTransferable,
-} 
\ No newline at end of file
+    
+    #[msg("Token account does not hold this memory NFT")]
+    TokenNotHeld,
+    
+    #[msg("This memory NFT is soulbound and can never be made transferable")]
+    Soulbound,
+    
+    #[msg("Not authorized to perform this action")]
+    NotAuthorized,
+    
+    #[msg("Content hash cannot be empty")]
+    EmptyContentHash,
+    
+    #[msg("Not the curator of this collection")]
+    NotCollectionCurator,
+    
+    #[msg("Memory already belongs to a collection")]
+    AlreadyInCollection,
+    
+    #[msg("Memory does not belong to this collection")]
+    NotInCollection,
+    
+    #[msg("Memory type does not match the collection")]
+    MemoryTypeMismatch,
+    
+    #[msg("Collection accounts are required for memories in a collection")]
+    MissingCollectionAccounts,
+    
+    #[msg("Collection account does not match the memory's collection")]
+    InvalidCollectionAccount,
+    
+    #[msg("Fingerprint record belongs to a different memory")]
+    InvalidFingerprintRecord,
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_data_len_matches_serialized_size() {
+        let memory_data = MemoryData {
+            authority: Pubkey::new_unique(),
+            memory_count: u64::MAX,
+            bump: 255,
+        };
+        assert_eq!(memory_data.try_to_vec().unwrap().len(), MemoryData::LEN);
+    }
+
+    #[test]
+    fn memory_len_matches_serialized_size() {
+        let memory = Memory {
+            owner: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            memory_type: MemoryType::Therapeutic,
+            quality: 4,
+            neural_fingerprint: [7; 32],
+            captured_at: i64::MAX,
+            created_at: i64::MAX,
+            is_transferable: true,
+            is_soulbound: false,
+            memory_data: Pubkey::new_unique(),
+            collection: Some(Pubkey::new_unique()),
+            bump: 255,
+        };
+        assert_eq!(memory.try_to_vec().unwrap().len(), Memory::LEN);
+    }
+
+    #[test]
+    fn memory_history_len_fits_a_full_history() {
+        let entry = UriRecord {
+            uri: "u".repeat(MAX_URI_LENGTH),
+            content_hash: [7; 32],
+            replaced_at: i64::MAX,
+        };
+        let memory_history = MemoryHistory {
+            memory: Pubkey::new_unique(),
+            content_hash: [7; 32],
+            version: u32::MAX,
+            entries: vec![entry; MemoryHistory::MAX_ENTRIES],
+            bump: 255,
+        };
+        assert_eq!(memory_history.try_to_vec().unwrap().len(), MemoryHistory::LEN);
+    }
+
+    #[test]
+    fn memory_collection_len_matches_serialized_size() {
+        let memory_collection = MemoryCollection {
+            collection_mint: Pubkey::new_unique(),
+            curator: Pubkey::new_unique(),
+            memory_type: Some(MemoryType::Cultural),
+            item_count: u64::MAX,
+            created_at: i64::MAX,
+            bump: 255,
+        };
+        assert_eq!(memory_collection.try_to_vec().unwrap().len(), MemoryCollection::LEN);
+    }
+
+    #[test]
+    fn fingerprint_record_len_matches_serialized_size() {
+        let record = FingerprintRecord {
+            neural_fingerprint: [7; 32],
+            mint: Pubkey::new_unique(),
+            memory: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(record.try_to_vec().unwrap().len(), FingerprintRecord::LEN);
+    }
+}
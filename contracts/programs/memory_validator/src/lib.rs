@@ -1,12 +1,25 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use memory_nft::program::MemoryNft;
+use memory_nft::Memory;
+use nram_token::program::NramToken;
+use nram_token::TokenConfig;
 
-declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+declare_id!("J3dSk82HQaUzS7bzEp7neYtwEv6V8xzjEwLG1WYM8fmH");
+
+/// Reputation of a new validator, and the level it decays back to
+pub const REPUTATION_NEUTRAL: u16 = 5_000;
+pub const REPUTATION_MAX: u16 = 10_000;
+
+/// Fixed-point scale of `Validator::reward_per_share`
+pub const REWARD_PER_SHARE_PRECISION: u128 = 1_000_000_000_000;
 
 #[program]
 pub mod memory_validator {
     use super::*;
     
+    #[allow(clippy::result_large_err)]
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let validator_config = &mut ctx.accounts.validator_config;
         validator_config.authority = ctx.accounts.authority.key();
@@ -15,13 +28,40 @@ pub mod memory_validator {
         validator_config.validator_count = 0;
         validator_config.min_stake_amount = 1000; // Default minimum stake
         validator_config.reward_per_validation = 10; // Default reward
+        validator_config.crank_tip = 0; // No tip for processing by default
         validator_config.validation_threshold = 3; // Default threshold
+        validator_config.unbonding_slots = 216_000; // About a day of slots
+        validator_config.commit_period = 24 * 60 * 60; // Default one day to commit
+        validator_config.reveal_period = 24 * 60 * 60; // Default one day to reveal
+        validator_config.max_quality_deviation = 3; // Default tolerance around the consensus score
+        validator_config.max_deviations = 3; // Default disagreements in a row before a slash
+        validator_config.slash_bps = 500; // Default 5% of stake
+        validator_config.burn_slashed = false;
+        validator_config.appeal_window = 3 * 24 * 60 * 60; // Default three days
+        validator_config.pending_slashed = 0;
+        validator_config.stake_weighted = false; // One validator, one vote
+        validator_config.max_vote_weight = 0; // No cap on stake weight
+        validator_config.reputation_half_life = 30 * 24 * 60 * 60; // Default thirty days
+        validator_config.reputation_gain = 100;
+        validator_config.reputation_penalty = 200;
+        validator_config.jail_window = 7 * 24 * 60 * 60; // Default seven days without a vote
+        validator_config.jail_period = 24 * 60 * 60; // Default one day
+        validator_config.unjail_fee = 0;
         validator_config.bump = *ctx.bumps.get("validator_config").unwrap();
         
+        emit!(ValidatorConfigInitialized {
+            validator_config: validator_config.key(),
+            authority: validator_config.authority,
+            staking_token_mint: validator_config.staking_token_mint,
+            reward_token_mint: validator_config.reward_token_mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Memory validator program initialized");
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
     pub fn register_validator(ctx: Context<RegisterValidator>, stake_amount: u64) -> Result<()> {
         let validator_config = &mut ctx.accounts.validator_config;
         
@@ -31,6 +71,19 @@ pub mod memory_validator {
             ErrorCode::InsufficientStake
         );
         
+        // Escrow the stake in the validator's vault
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.staking_token_account.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            stake_amount,
+        )?;
+        
         // Update validator count
         validator_config.validator_count = validator_config.validator_count.checked_add(1).unwrap();
         
@@ -42,71 +95,590 @@ pub mod memory_validator {
         validator.rewards_earned = 0;
         validator.is_active = true;
         validator.last_validation = 0;
+        validator.active_since = Clock::get()?.unix_timestamp;
+        validator.jailed_until = 0;
+        validator.unbonding_until = 0;
+        validator.deviations = 0;
+        validator.slash_count = 0;
+        validator.open_validations = 0;
+        validator.reputation = REPUTATION_NEUTRAL;
+        validator.reputation_updated_at = validator.active_since;
+        validator.delegated_stake = 0;
+        validator.commission_bps = 0;
+        validator.reward_per_share = 0;
         validator.validator_config = validator_config.key();
         validator.bump = *ctx.bumps.get("validator").unwrap();
         
+        emit!(ValidatorRegistered {
+            validator: validator.key(),
+            authority: validator.authority,
+            stake_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Validator registered with stake amount: {}", stake_amount);
         Ok(())
     }
     
-    pub fn submit_validation(
-        ctx: Context<SubmitValidation>,
-        memory_id: Pubkey,
-        is_valid: bool,
-        quality_score: u8,
-        timestamp: i64,
-    ) -> Result<()> {
+    #[allow(clippy::result_large_err)]
+    pub fn commit_validation(ctx: Context<CommitValidation>, commitment: [u8; 32]) -> Result<()> {
         // Ensure validator is active
         let validator = &mut ctx.accounts.validator;
         require!(validator.is_active, ErrorCode::ValidatorInactive);
         
-        // Check quality score range
-        require!(quality_score <= 10, ErrorCode::InvalidQualityScore);
+        // Holders cannot vouch for their own memories. The cached owner on the
+        // memory goes stale on plain SPL transfers, so check the token itself.
+        let memory_id = ctx.accounts.memory.key();
+        require!(
+            ctx.accounts.memory_token_account.owner != ctx.accounts.authority.key(),
+            ErrorCode::CannotValidateOwnMemory
+        );
         
-        // Create validation record
+        let timestamp = Clock::get()?.unix_timestamp;
+        let validator_config = &ctx.accounts.validator_config;
+        
+        // The first commitment opens the round's commit phase. A round that
+        // expired without enough reveals opens again for a fresh vote, once
+        // every vote cast in it has been processed or penalized.
+        let validation_round = &mut ctx.accounts.validation_round;
+        if validation_round.memory_id == Pubkey::default() {
+            validation_round.memory_id = memory_id;
+            validation_round.validator_config = validator_config.key();
+            validation_round.bump = *ctx.bumps.get("validation_round").unwrap();
+            validation_round.open(validator_config, timestamp);
+        } else if validation_round.status == RoundStatus::Expired {
+            require!(validation_round.unsettled == 0, ErrorCode::RoundNotSettled);
+            validation_round.round = validation_round.round.checked_add(1).unwrap();
+            validation_round.open(validator_config, timestamp);
+            
+            msg!("Validation round {} opened for memory: {}", validation_round.round, memory_id);
+        }
+        require!(!validation_round.is_finalized(), ErrorCode::RoundFinalized);
+        
+        // Each validator votes once per round, so rewards are only ever paid
+        // once per distinct memory
         let validation = &mut ctx.accounts.validation;
+        require!(
+            validation.validator == Pubkey::default() || validation.round != validation_round.round,
+            ErrorCode::DuplicateValidation
+        );
+        require!(
+            validation_round.status == RoundStatus::Commit
+                && timestamp <= validation_round.commit_deadline,
+            ErrorCode::CommitPeriodOver
+        );
+        
+        // Record the sealed vote
         validation.validator = validator.key();
         validation.memory_id = memory_id;
-        validation.is_valid = is_valid;
-        validation.quality_score = quality_score;
+        validation.round = validation_round.round;
+        validation.commitment = commitment;
+        validation.is_revealed = false;
         validation.timestamp = timestamp;
         validation.is_processed = false;
-        validation.validator_config = ctx.accounts.validator_config.key();
+        validation.validator_config = validator_config.key();
         validation.bump = *ctx.bumps.get("validation").unwrap();
         
-        // Update validator stats
+        // Update validator stats, settling any reputation decay since the
+        // last vote before the clock restarts
+        validator.settle_reputation(validator_config.reputation_half_life, timestamp);
         validator.validations_performed = validator.validations_performed.checked_add(1).unwrap();
+        validator.open_validations = validator.open_validations.checked_add(1).unwrap();
         validator.last_validation = timestamp;
         
-        msg!("Validation submitted for memory: {}", memory_id);
+        // Fix the vote's weight now, so stake delegated before the reveal
+        // can't inflate it
+        validation.weight = validator_config.vote_weight(validator.total_stake(), validator.reputation);
+        
+        // The commit phase runs until its deadline however many commitments
+        // arrive, so early committers cannot close the round on everyone else
+        validation_round.commits = validation_round.commits.checked_add(1).unwrap();
+        validation_round.unsettled = validation_round.unsettled.checked_add(1).unwrap();
+        
+        emit!(ValidationCommitted {
+            validation: validation.key(),
+            validator: validator.key(),
+            memory_id,
+            commitment,
+            reveal_deadline: validation_round.reveal_deadline,
+            timestamp,
+        });
+        
+        msg!("Validation committed for memory: {}", memory_id);
         Ok(())
     }
     
-    pub fn process_validation(ctx: Context<ProcessValidation>) -> Result<()> {
-        // Ensure only authority can process
+    #[allow(clippy::result_large_err)]
+    pub fn reveal_validation(
+        ctx: Context<RevealValidation>,
+        is_valid: bool,
+        quality_score: u8,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        // Check quality score range
+        require!(quality_score <= 10, ErrorCode::InvalidQualityScore);
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        // Reveals open once the commit phase is over
+        let validation_round = &mut ctx.accounts.validation_round;
+        require!(!validation_round.is_finalized(), ErrorCode::RoundFinalized);
+        if validation_round.status == RoundStatus::Commit && timestamp > validation_round.commit_deadline {
+            validation_round.status = RoundStatus::Reveal;
+        }
+        require!(validation_round.status == RoundStatus::Reveal, ErrorCode::RevealNotOpen);
         require!(
-            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
-            ErrorCode::NotAuthorized
+            timestamp <= validation_round.reveal_deadline,
+            ErrorCode::RevealPeriodOver
+        );
+        
+        // The vote must match what was committed
+        let validation = &mut ctx.accounts.validation;
+        require!(!validation.is_revealed, ErrorCode::AlreadyRevealed);
+        // The commitment binds the validator and the memory, so a copied
+        // commitment can't be opened with someone else's reveal
+        require!(
+            vote_commitment(
+                &validation.validator,
+                &validation.memory_id,
+                is_valid,
+                quality_score,
+                &salt
+            ) == validation.commitment,
+            ErrorCode::InvalidReveal
+        );
+        let weight = validation.weight;
+        validation.is_valid = is_valid;
+        validation.quality_score = quality_score;
+        validation.is_revealed = true;
+        
+        // Count the vote in the memory's round
+        if is_valid {
+            validation_round.valid_votes = validation_round.valid_votes.checked_add(1).unwrap();
+            validation_round.valid_weight = validation_round.valid_weight.checked_add(weight).unwrap();
+            validation_round.quality_weights[quality_score as usize] =
+                validation_round.quality_weights[quality_score as usize].checked_add(weight).unwrap();
+        } else {
+            validation_round.invalid_votes = validation_round.invalid_votes.checked_add(1).unwrap();
+            validation_round.invalid_weight = validation_round.invalid_weight.checked_add(weight).unwrap();
+        }
+        
+        emit!(ValidationRevealed {
+            validation: validation.key(),
+            validator: validation.validator,
+            memory_id: validation.memory_id,
+            is_valid,
+            quality_score,
+            weight,
+            timestamp,
+        });
+        
+        // Finalize the round as soon as every commitment is revealed
+        if validation_round.total_votes() == validation_round.commits {
+            validation_round.finalize(ctx.accounts.validator_config.validation_threshold, timestamp);
+            
+            emit!(ValidationRoundFinalized {
+                validation_round: validation_round.key(),
+                memory_id: validation_round.memory_id,
+                status: validation_round.status,
+                valid_votes: validation_round.valid_votes,
+                invalid_votes: validation_round.invalid_votes,
+                valid_weight: validation_round.valid_weight,
+                invalid_weight: validation_round.invalid_weight,
+                quality_score: validation_round.quality_score,
+                timestamp,
+            });
+        }
+        
+        msg!("Validation revealed for memory: {}", validation_round.memory_id);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn finalize_round(ctx: Context<FinalizeRound>) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        // Anyone can close a round whose reveal phase has run out
+        let validation_round = &mut ctx.accounts.validation_round;
+        require!(!validation_round.is_finalized(), ErrorCode::RoundFinalized);
+        require!(
+            timestamp > validation_round.reveal_deadline,
+            ErrorCode::RevealPeriodNotOver
         );
+        validation_round.finalize(ctx.accounts.validator_config.validation_threshold, timestamp);
+        
+        emit!(ValidationRoundFinalized {
+            validation_round: validation_round.key(),
+            memory_id: validation_round.memory_id,
+            status: validation_round.status,
+            valid_votes: validation_round.valid_votes,
+            invalid_votes: validation_round.invalid_votes,
+            valid_weight: validation_round.valid_weight,
+            invalid_weight: validation_round.invalid_weight,
+            quality_score: validation_round.quality_score,
+            timestamp,
+        });
+        
+        msg!("Validation round finalized for memory: {}", validation_round.memory_id);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn penalize_unrevealed(ctx: Context<PenalizeUnrevealed>) -> Result<()> {
+        require!(ctx.accounts.validation_round.is_finalized(), ErrorCode::RoundNotFinalized);
+        
+        // A commitment that was never revealed counts as a full set of
+        // disagreements, so the validator becomes slashable straight away
+        let validation = &mut ctx.accounts.validation;
+        validation.is_processed = true;
+        let validation_round = &mut ctx.accounts.validation_round;
+        validation_round.unsettled = validation_round.unsettled.checked_sub(1).unwrap();
+        
+        let validator = &mut ctx.accounts.validator;
+        validator.open_validations = validator.open_validations.checked_sub(1).unwrap();
+        validator.deviations = validator
+            .deviations
+            .saturating_add(ctx.accounts.validator_config.max_deviations);
+        validator.adjust_reputation(false, &ctx.accounts.validator_config, Clock::get()?.unix_timestamp);
+        
+        emit!(UnrevealedValidationPenalized {
+            validation: validation.key(),
+            validator: validator.key(),
+            memory_id: validation.memory_id,
+            deviations: validator.deviations,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Unrevealed validation penalized for memory: {}", validation.memory_id);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn process_validation(ctx: Context<ProcessValidation>) -> Result<()> {
+        // Anyone can process a validation; votes are judged against the
+        // round's outcome rather than trusted from the caller. The vote was
+        // cast while the validator was active, so it still counts after the
+        // validator unbonds or is jailed.
+        let validation_round = &mut ctx.accounts.validation_round;
+        require!(validation_round.is_finalized(), ErrorCode::RoundNotFinalized);
         
         // Mark validation as processed
         let validation = &mut ctx.accounts.validation;
         validation.is_processed = true;
+        validation_round.unsettled = validation_round.unsettled.checked_sub(1).unwrap();
         
-        // Reward validator
+        // Reward votes that agree with consensus; count the ones that don't
+        // towards a slash. Expired rounds have no consensus to judge against.
+        // The reward scales with reputation, and the vote then moves it
+        let validator_config = &ctx.accounts.validator_config;
         let validator = &mut ctx.accounts.validator;
-        let reward_amount = ctx.accounts.validator_config.reward_per_validation;
-        validator.rewards_earned = validator.rewards_earned.checked_add(reward_amount).unwrap();
+        validator.open_validations = validator.open_validations.checked_sub(1).unwrap();
+        let agrees = validation_round.agrees_with(
+            validation.is_valid,
+            validation.quality_score,
+            validator_config.max_quality_deviation,
+        );
+        let timestamp = Clock::get()?.unix_timestamp;
+        let reputation = validator.current_reputation(validator_config.reputation_half_life, timestamp);
+        let reward_amount = if validation_round.status == RoundStatus::Expired {
+            0
+        } else if agrees {
+            validator.deviations = 0;
+            validator.adjust_reputation(true, validator_config, timestamp);
+            scale_by_reputation(validator_config.reward_per_validation, reputation)
+        } else {
+            validator.deviations = validator.deviations.saturating_add(1);
+            validator.adjust_reputation(false, validator_config, timestamp);
+            0
+        };
+        
+        // Delegators get their pro rata part, less the validator's commission
+        let validator_reward = validator.distribute_reward(reward_amount);
+        validator.rewards_earned = validator.rewards_earned.checked_add(validator_reward).unwrap();
+        
+        // Tip whoever cranked it
+        let tip = ctx.accounts.validator_config.crank_tip;
+        if tip > 0 {
+            mint_rewards(
+                &ctx.accounts.validator_config,
+                &ctx.accounts.token_config,
+                &ctx.accounts.reward_token_mint,
+                &ctx.accounts.cranker_token_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+                &ctx.accounts.nram_token_program,
+                tip,
+            )?;
+        }
+        
+        emit!(ValidationProcessed {
+            validation: ctx.accounts.validation.key(),
+            validator: ctx.accounts.validator.key(),
+            memory_id: ctx.accounts.validation.memory_id,
+            reward_amount,
+            delegator_rewards: reward_amount - validator_reward,
+            reputation: ctx.accounts.validator.reputation,
+            cranker: ctx.accounts.cranker.key(),
+            tip,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
         msg!("Validation processed and rewarded: {} tokens", reward_amount);
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
+    pub fn slash_validator(ctx: Context<SlashValidator>) -> Result<()> {
+        require!(
+            ctx.accounts.validator.deviations >= ctx.accounts.validator_config.max_deviations,
+            ErrorCode::SlashThresholdNotReached
+        );
+        
+        // Move the slashed share of the stake into the treasury, where it
+        // waits out the appeal window
+        let amount = (ctx.accounts.validator.stake_amount as u128)
+            .checked_mul(ctx.accounts.validator_config.slash_bps as u128)
+            .unwrap()
+            .checked_div(10_000)
+            .unwrap() as u64;
+        let validator_authority = ctx.accounts.validator.authority;
+        let validator_seeds: &[&[u8]] = &[
+            b"validator",
+            validator_authority.as_ref(),
+            &[ctx.accounts.validator.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.validator.to_account_info(),
+                },
+                &[validator_seeds],
+            ),
+            amount,
+        )?;
+        
+        // Hold the slashed tokens back from treasury withdrawals until the appeal ends
+        let validator_config = &mut ctx.accounts.validator_config;
+        validator_config.pending_slashed = validator_config.pending_slashed.checked_add(amount).unwrap();
+        
+        let validator = &mut ctx.accounts.validator;
+        let index = validator.slash_count;
+        validator.stake_amount = validator.stake_amount.checked_sub(amount).unwrap();
+        validator.deviations = 0;
+        validator.slash_count = validator.slash_count.checked_add(1).unwrap();
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        let slash_record = &mut ctx.accounts.slash_record;
+        slash_record.validator = validator.key();
+        slash_record.index = index;
+        slash_record.amount = amount;
+        slash_record.burn = validator_config.burn_slashed;
+        slash_record.slashed_at = timestamp;
+        slash_record.appeal_deadline = timestamp.checked_add(validator_config.appeal_window).unwrap();
+        slash_record.status = SlashStatus::Pending;
+        slash_record.bump = *ctx.bumps.get("slash_record").unwrap();
+        
+        emit!(ValidatorSlashed {
+            validator: validator.key(),
+            slash_record: slash_record.key(),
+            amount,
+            remaining_stake: validator.stake_amount,
+            appeal_deadline: slash_record.appeal_deadline,
+            timestamp,
+        });
+        
+        msg!("Validator slashed: {} tokens", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn reverse_slash(ctx: Context<ReverseSlash>) -> Result<()> {
+        // Ensure only authority can reverse
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= ctx.accounts.slash_record.appeal_deadline,
+            ErrorCode::AppealWindowClosed
+        );
+        
+        // Give the slashed tokens back to the validator's stake
+        let amount = ctx.accounts.slash_record.amount;
+        let config_seeds: &[&[u8]] = &[b"validator-config", &[ctx.accounts.validator_config.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.validator_config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            amount,
+        )?;
+        
+        let validator_config = &mut ctx.accounts.validator_config;
+        validator_config.pending_slashed = validator_config.pending_slashed.checked_sub(amount).unwrap();
+        
+        let validator = &mut ctx.accounts.validator;
+        validator.stake_amount = validator.stake_amount.checked_add(amount).unwrap();
+        
+        let slash_record = &mut ctx.accounts.slash_record;
+        slash_record.status = SlashStatus::Reversed;
+        
+        emit!(SlashReversed {
+            validator: validator.key(),
+            slash_record: slash_record.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Slash reversed: {} tokens returned", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn finalize_slash(ctx: Context<FinalizeSlash>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp > ctx.accounts.slash_record.appeal_deadline,
+            ErrorCode::AppealWindowOpen
+        );
+        
+        // Burn the slashed tokens if the config asked for it when the slash
+        // happened; otherwise they stay in the treasury
+        let amount = ctx.accounts.slash_record.amount;
+        if ctx.accounts.slash_record.burn {
+            let config_seeds: &[&[u8]] = &[b"validator-config", &[ctx.accounts.validator_config.bump]];
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.staking_token_mint.to_account_info(),
+                        from: ctx.accounts.treasury.to_account_info(),
+                        authority: ctx.accounts.validator_config.to_account_info(),
+                    },
+                    &[config_seeds],
+                ),
+                amount,
+            )?;
+        }
+        
+        // Unburned tokens are now free for the authority to withdraw
+        let validator_config = &mut ctx.accounts.validator_config;
+        validator_config.pending_slashed = validator_config.pending_slashed.checked_sub(amount).unwrap();
+        
+        let slash_record = &mut ctx.accounts.slash_record;
+        slash_record.status = SlashStatus::Finalized;
+        
+        emit!(SlashFinalized {
+            validator: slash_record.validator,
+            slash_record: slash_record.key(),
+            amount,
+            burned: slash_record.burn,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Slash finalized: {} tokens", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        // Ensure only authority can withdraw
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        // Slashes still open to appeal may have to be paid back
+        let available = ctx
+            .accounts
+            .treasury
+            .amount
+            .checked_sub(ctx.accounts.validator_config.pending_slashed)
+            .unwrap();
+        require!(
+            amount > 0 && amount <= available,
+            ErrorCode::InsufficientTreasuryBalance
+        );
+        
+        let config_seeds: &[&[u8]] = &[b"validator-config", &[ctx.accounts.validator_config.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.validator_config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            amount,
+        )?;
+        
+        emit!(TreasuryWithdrawn {
+            validator_config: ctx.accounts.validator_config.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Treasury withdrawal: {} tokens", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn finalize_memory_quality(ctx: Context<FinalizeMemoryQuality>) -> Result<()> {
+        // Only accepted rounds write their verdict back to the memory
+        let validation_round = &ctx.accounts.validation_round;
+        require!(validation_round.is_finalized(), ErrorCode::RoundNotFinalized);
+        require!(
+            validation_round.status == RoundStatus::Accepted,
+            ErrorCode::MemoryRejected
+        );
+        
+        let quality = quality_tier(validation_round.quality_score);
+        
+        let config_seeds: &[&[u8]] = &[b"validator-config", &[ctx.accounts.validator_config.bump]];
+        memory_nft::cpi::set_memory_quality(
+            CpiContext::new_with_signer(
+                ctx.accounts.memory_nft_program.to_account_info(),
+                memory_nft::cpi::accounts::SetMemoryQuality {
+                    validator_authority: ctx.accounts.validator_config.to_account_info(),
+                    memory: ctx.accounts.memory.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            quality,
+        )?;
+        
+        emit!(MemoryQualityFinalized {
+            memory: ctx.accounts.memory.key(),
+            validation_round: validation_round.key(),
+            quality_score: validation_round.quality_score,
+            quality,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Memory quality finalized at tier {}", quality);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err, clippy::too_many_arguments)]
     pub fn update_validator_config(
         ctx: Context<UpdateValidatorConfig>,
         min_stake_amount: Option<u64>,
         reward_per_validation: Option<u64>,
         validation_threshold: Option<u8>,
+        unbonding_slots: Option<u64>,
+        commit_period: Option<i64>,
+        reveal_period: Option<i64>,
+        crank_tip: Option<u64>,
     ) -> Result<()> {
         // Ensure only authority can update
         require!(
@@ -125,24 +697,344 @@ pub mod memory_validator {
         }
         
         if let Some(threshold) = validation_threshold {
+            require!(threshold > 0, ErrorCode::InvalidThreshold);
             config.validation_threshold = threshold;
         }
         
+        if let Some(slots) = unbonding_slots {
+            config.unbonding_slots = slots;
+        }
+        
+        if let Some(period) = commit_period {
+            require!(period > 0, ErrorCode::InvalidPeriod);
+            config.commit_period = period;
+        }
+        
+        if let Some(period) = reveal_period {
+            require!(period > 0, ErrorCode::InvalidPeriod);
+            config.reveal_period = period;
+        }
+        
+        if let Some(tip) = crank_tip {
+            config.crank_tip = tip;
+        }
+        
+        emit!(ValidatorConfigUpdated {
+            validator_config: config.key(),
+            min_stake_amount: config.min_stake_amount,
+            reward_per_validation: config.reward_per_validation,
+            validation_threshold: config.validation_threshold,
+            unbonding_slots: config.unbonding_slots,
+            commit_period: config.commit_period,
+            reveal_period: config.reveal_period,
+            crank_tip: config.crank_tip,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Validator configuration updated");
         Ok(())
     }
     
-    pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+    #[allow(clippy::result_large_err)]
+    pub fn update_slashing_config(
+        ctx: Context<UpdateValidatorConfig>,
+        max_quality_deviation: Option<u8>,
+        max_deviations: Option<u8>,
+        slash_bps: Option<u16>,
+        burn_slashed: Option<bool>,
+        appeal_window: Option<i64>,
+    ) -> Result<()> {
+        // Ensure only authority can update
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        let config = &mut ctx.accounts.validator_config;
+        
+        if let Some(deviation) = max_quality_deviation {
+            config.max_quality_deviation = deviation;
+        }
+        
+        if let Some(deviations) = max_deviations {
+            require!(deviations > 0, ErrorCode::InvalidThreshold);
+            config.max_deviations = deviations;
+        }
+        
+        if let Some(bps) = slash_bps {
+            require!(bps <= 10_000, ErrorCode::InvalidSlashRate);
+            config.slash_bps = bps;
+        }
+        
+        if let Some(burn) = burn_slashed {
+            config.burn_slashed = burn;
+        }
+        
+        if let Some(window) = appeal_window {
+            require!(window >= 0, ErrorCode::InvalidAppealWindow);
+            config.appeal_window = window;
+        }
+        
+        emit!(SlashingConfigUpdated {
+            validator_config: config.key(),
+            max_quality_deviation: config.max_quality_deviation,
+            max_deviations: config.max_deviations,
+            slash_bps: config.slash_bps,
+            burn_slashed: config.burn_slashed,
+            appeal_window: config.appeal_window,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Slashing configuration updated");
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn update_consensus_config(
+        ctx: Context<UpdateValidatorConfig>,
+        stake_weighted: Option<bool>,
+        max_vote_weight: Option<u64>,
+    ) -> Result<()> {
+        // Ensure only authority can update
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        let config = &mut ctx.accounts.validator_config;
+        
+        if let Some(weighted) = stake_weighted {
+            config.stake_weighted = weighted;
+        }
+        
+        if let Some(cap) = max_vote_weight {
+            config.max_vote_weight = cap;
+        }
+        
+        emit!(ConsensusConfigUpdated {
+            validator_config: config.key(),
+            stake_weighted: config.stake_weighted,
+            max_vote_weight: config.max_vote_weight,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Consensus configuration updated");
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn update_reputation_config(
+        ctx: Context<UpdateValidatorConfig>,
+        reputation_half_life: Option<i64>,
+        reputation_gain: Option<u16>,
+        reputation_penalty: Option<u16>,
+    ) -> Result<()> {
+        // Ensure only authority can update
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        let config = &mut ctx.accounts.validator_config;
+        
+        if let Some(half_life) = reputation_half_life {
+            require!(half_life >= 0, ErrorCode::InvalidReputationConfig);
+            config.reputation_half_life = half_life;
+        }
+        
+        if let Some(gain) = reputation_gain {
+            require!(gain <= REPUTATION_MAX, ErrorCode::InvalidReputationConfig);
+            config.reputation_gain = gain;
+        }
+        
+        if let Some(penalty) = reputation_penalty {
+            require!(penalty <= REPUTATION_MAX, ErrorCode::InvalidReputationConfig);
+            config.reputation_penalty = penalty;
+        }
+        
+        emit!(ReputationConfigUpdated {
+            validator_config: config.key(),
+            reputation_half_life: config.reputation_half_life,
+            reputation_gain: config.reputation_gain,
+            reputation_penalty: config.reputation_penalty,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Reputation configuration updated");
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn request_unstake(ctx: Context<RequestUnstake>) -> Result<()> {
+        let validator_config = &mut ctx.accounts.validator_config;
         let validator = &mut ctx.accounts.validator;
         
-        // Mark validator as inactive
+        // Jailed validators can leave too; they were already taken out of the count
+        let was_active = validator.is_active;
+        require!(
+            was_active || (validator.is_jailed() && validator.unbonding_until == 0),
+            ErrorCode::ValidatorInactive
+        );
+        
+        // Stop validating now; the stake stays in the vault until the
+        // unbonding period is over
+        let unbonding_until = Clock::get()?
+            .slot
+            .checked_add(validator_config.unbonding_slots)
+            .unwrap();
         validator.is_active = false;
+        validator.unbonding_until = unbonding_until;
+        if was_active {
+            validator_config.validator_count = validator_config.validator_count.checked_sub(1).unwrap();
+        }
+        
+        emit!(ValidatorUnstaked {
+            validator: validator.key(),
+            authority: validator.authority,
+            stake_amount: validator.stake_amount,
+            unbonding_until,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Validator unbonding until slot {}", unbonding_until);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        let validator = &ctx.accounts.validator;
+        require!(
+            !validator.is_active && validator.unbonding_until != 0,
+            ErrorCode::NotUnbonding
+        );
+        require!(
+            Clock::get()?.slot >= validator.unbonding_until,
+            ErrorCode::UnbondingNotComplete
+        );
+        
+        // The stake stays slashable until every vote is judged and any slash
+        // it earned has been applied
+        require!(validator.open_validations == 0, ErrorCode::OpenValidations);
+        require!(
+            validator.deviations < ctx.accounts.validator_config.max_deviations,
+            ErrorCode::SlashPending
+        );
+        
+        // Return the escrowed stake, signed by the validator PDA that owns the vault
+        let amount = validator.stake_amount;
+        let authority_key = ctx.accounts.authority.key();
+        let validator_seeds: &[&[u8]] = &[
+            b"validator",
+            authority_key.as_ref(),
+            &[validator.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to: ctx.accounts.staking_token_account.to_account_info(),
+                    authority: ctx.accounts.validator.to_account_info(),
+                },
+                &[validator_seeds],
+            ),
+            amount,
+        )?;
+        
+        let validator = &mut ctx.accounts.validator;
         validator.stake_amount = 0;
+        validator.unbonding_until = 0;
+        
+        emit!(StakeWithdrawn {
+            validator: validator.key(),
+            authority: validator.authority,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Withdrew {} staked tokens", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn reactivate_validator(ctx: Context<ReactivateValidator>, stake_amount: u64) -> Result<()> {
+        require!(!ctx.accounts.validator.is_active, ErrorCode::ValidatorActive);
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        // A jailed validator that has since unstaked can't meet the minimum
+        // to unjail, so it comes back through here once the jail period is
+        // over, paying the same fee
+        let was_jailed = ctx.accounts.validator.is_jailed();
+        let fee = if was_jailed { ctx.accounts.validator_config.unjail_fee } else { 0 };
+        if was_jailed {
+            require!(
+                timestamp >= ctx.accounts.validator.jailed_until,
+                ErrorCode::JailPeriodNotOver
+            );
+        }
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.staking_token_account.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+        
+        // Top up the vault; stake still unbonding counts towards the minimum
+        if stake_amount > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.staking_token_account.to_account_info(),
+                        to: ctx.accounts.stake_vault.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                stake_amount,
+            )?;
+        }
         
-        msg!("Validator unstaked and set to inactive");
+        let validator_config = &mut ctx.accounts.validator_config;
+        let validator = &mut ctx.accounts.validator;
+        validator.stake_amount = validator.stake_amount.checked_add(stake_amount).unwrap();
+        require!(
+            validator.stake_amount >= validator_config.min_stake_amount,
+            ErrorCode::InsufficientStake
+        );
+        
+        validator.is_active = true;
+        validator.jailed_until = 0;
+        validator.unbonding_until = 0;
+        validator.active_since = timestamp;
+        validator_config.validator_count = validator_config.validator_count.checked_add(1).unwrap();
+        
+        if was_jailed {
+            emit!(ValidatorUnjailed {
+                validator: validator.key(),
+                authority: validator.authority,
+                fee,
+                timestamp,
+            });
+        }
+        emit!(ValidatorReactivated {
+            validator: validator.key(),
+            authority: validator.authority,
+            stake_amount: validator.stake_amount,
+            timestamp,
+        });
+        
+        msg!("Validator reactivated with stake amount: {}", validator.stake_amount);
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
     pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
         let validator = &mut ctx.accounts.validator;
         let rewards = validator.rewards_earned;
@@ -150,12 +1042,370 @@ pub mod memory_validator {
         // Reset rewards
         validator.rewards_earned = 0;
         
+        // Mint the rewards through nram_token so its circulating supply stays right
+        mint_rewards(
+            &ctx.accounts.validator_config,
+            &ctx.accounts.token_config,
+            &ctx.accounts.reward_token_mint,
+            &ctx.accounts.reward_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            &ctx.accounts.nram_token_program,
+            rewards,
+        )?;
+        
+        let validator = &ctx.accounts.validator;
+        emit!(RewardsClaimed {
+            validator: validator.key(),
+            authority: validator.authority,
+            amount: rewards,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Claimed {} reward tokens", rewards);
         Ok(())
     }
-}
-
-#[derive(Accounts)]
+    
+    #[allow(clippy::result_large_err)]
+    pub fn set_commission(ctx: Context<SetCommission>, commission_bps: u16) -> Result<()> {
+        require!(commission_bps <= 10_000, ErrorCode::InvalidCommission);
+        
+        let validator = &mut ctx.accounts.validator;
+        validator.commission_bps = commission_bps;
+        
+        emit!(CommissionUpdated {
+            validator: validator.key(),
+            commission_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Commission set to {} basis points", commission_bps);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn delegate(ctx: Context<Delegate>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidDelegationAmount);
+        require!(ctx.accounts.validator.is_active, ErrorCode::ValidatorInactive);
+        
+        // Move the tokens into the validator's delegation pool
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.delegator_token_account.to_account_info(),
+                    to: ctx.accounts.delegation_vault.to_account_info(),
+                    authority: ctx.accounts.delegator.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        
+        let validator = &mut ctx.accounts.validator;
+        let delegation = &mut ctx.accounts.delegation;
+        if delegation.delegator == Pubkey::default() {
+            delegation.delegator = ctx.accounts.delegator.key();
+            delegation.validator = validator.key();
+            delegation.bump = *ctx.bumps.get("delegation").unwrap();
+        }
+        
+        // Bank what the existing delegation earned before its size changes
+        delegation.settle(validator.reward_per_share);
+        delegation.amount = delegation.amount.checked_add(amount).unwrap();
+        delegation.reset_reward_debt(validator.reward_per_share);
+        validator.delegated_stake = validator.delegated_stake.checked_add(amount).unwrap();
+        
+        emit!(StakeDelegated {
+            delegation: delegation.key(),
+            validator: validator.key(),
+            delegator: delegation.delegator,
+            amount,
+            delegated_stake: validator.delegated_stake,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Delegated {} tokens", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn undelegate(ctx: Context<Undelegate>, amount: u64) -> Result<()> {
+        let validator = &mut ctx.accounts.validator;
+        let delegation = &mut ctx.accounts.delegation;
+        require!(amount > 0, ErrorCode::InvalidDelegationAmount);
+        
+        // One unbonding at a time, so a new request never pushes back the
+        // release of tokens that are already unbonding
+        require!(delegation.unbonding_amount == 0, ErrorCode::AlreadyUnbonding);
+        
+        delegation.settle(validator.reward_per_share);
+        delegation.amount = delegation
+            .amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InvalidDelegationAmount)?;
+        delegation.reset_reward_debt(validator.reward_per_share);
+        validator.delegated_stake = validator.delegated_stake.checked_sub(amount).unwrap();
+        
+        // The tokens stop counting straight away but wait out the same
+        // unbonding period as a validator's own stake
+        let unbonding_until = Clock::get()?
+            .slot
+            .checked_add(ctx.accounts.validator_config.unbonding_slots)
+            .unwrap();
+        delegation.unbonding_amount = amount;
+        delegation.unbonding_until = unbonding_until;
+        
+        emit!(StakeUndelegated {
+            delegation: delegation.key(),
+            validator: validator.key(),
+            delegator: delegation.delegator,
+            amount,
+            unbonding_until,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Undelegating {} tokens until slot {}", amount, unbonding_until);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn withdraw_delegation(ctx: Context<WithdrawDelegation>) -> Result<()> {
+        let delegation = &ctx.accounts.delegation;
+        require!(delegation.unbonding_amount > 0, ErrorCode::NotUnbonding);
+        require!(
+            Clock::get()?.slot >= delegation.unbonding_until,
+            ErrorCode::UnbondingNotComplete
+        );
+        
+        // Return the tokens, signed by the validator PDA that owns the pool
+        let amount = delegation.unbonding_amount;
+        let validator = &ctx.accounts.validator;
+        let validator_seeds: &[&[u8]] = &[
+            b"validator",
+            validator.authority.as_ref(),
+            &[validator.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.delegation_vault.to_account_info(),
+                    to: ctx.accounts.delegator_token_account.to_account_info(),
+                    authority: ctx.accounts.validator.to_account_info(),
+                },
+                &[validator_seeds],
+            ),
+            amount,
+        )?;
+        
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.unbonding_amount = 0;
+        delegation.unbonding_until = 0;
+        
+        emit!(DelegationWithdrawn {
+            delegation: delegation.key(),
+            validator: delegation.validator,
+            delegator: delegation.delegator,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Withdrew {} delegated tokens", amount);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn claim_delegation_rewards(ctx: Context<ClaimDelegationRewards>) -> Result<()> {
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.settle(ctx.accounts.validator.reward_per_share);
+        delegation.reset_reward_debt(ctx.accounts.validator.reward_per_share);
+        let rewards = delegation.pending_rewards;
+        require!(rewards > 0, ErrorCode::NoRewardsToClaim);
+        delegation.pending_rewards = 0;
+        
+        mint_rewards(
+            &ctx.accounts.validator_config,
+            &ctx.accounts.token_config,
+            &ctx.accounts.reward_token_mint,
+            &ctx.accounts.reward_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            &ctx.accounts.nram_token_program,
+            rewards,
+        )?;
+        
+        let delegation = &ctx.accounts.delegation;
+        emit!(DelegationRewardsClaimed {
+            delegation: delegation.key(),
+            validator: delegation.validator,
+            delegator: delegation.delegator,
+            amount: rewards,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Claimed {} delegation reward tokens", rewards);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn jail_validator(ctx: Context<JailValidator>) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let validator_config = &mut ctx.accounts.validator_config;
+        let validator = &mut ctx.accounts.validator;
+        require!(validator.is_active, ErrorCode::ValidatorInactive);
+        
+        // Anyone can jail a validator that has gone quiet for the whole window
+        let last_seen = validator.last_validation.max(validator.active_since);
+        require!(
+            timestamp > last_seen.checked_add(validator_config.jail_window).unwrap(),
+            ErrorCode::ValidatorNotIdle
+        );
+        
+        let jailed_until = timestamp.checked_add(validator_config.jail_period).unwrap();
+        validator.is_active = false;
+        validator.jailed_until = jailed_until;
+        validator_config.validator_count = validator_config.validator_count.checked_sub(1).unwrap();
+        
+        emit!(ValidatorJailed {
+            validator: validator.key(),
+            authority: validator.authority,
+            last_validation: validator.last_validation,
+            jailed_until,
+            timestamp,
+        });
+        
+        msg!("Validator jailed until {}", jailed_until);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn unjail(ctx: Context<Unjail>) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let validator = &ctx.accounts.validator;
+        require!(validator.is_jailed(), ErrorCode::NotJailed);
+        require!(timestamp >= validator.jailed_until, ErrorCode::JailPeriodNotOver);
+        require!(
+            validator.stake_amount >= ctx.accounts.validator_config.min_stake_amount,
+            ErrorCode::InsufficientStake
+        );
+        
+        // The fee goes to the treasury alongside slashed stake
+        let fee = ctx.accounts.validator_config.unjail_fee;
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.staking_token_account.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+        
+        // A validator that started unbonding while jailed stays out until it
+        // reactivates
+        let validator_config = &mut ctx.accounts.validator_config;
+        let validator = &mut ctx.accounts.validator;
+        validator.jailed_until = 0;
+        if validator.unbonding_until == 0 {
+            validator.is_active = true;
+            validator.active_since = timestamp;
+            validator_config.validator_count = validator_config.validator_count.checked_add(1).unwrap();
+        }
+        
+        emit!(ValidatorUnjailed {
+            validator: validator.key(),
+            authority: validator.authority,
+            fee,
+            timestamp,
+        });
+        
+        msg!("Validator unjailed");
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    pub fn update_jail_config(
+        ctx: Context<UpdateValidatorConfig>,
+        jail_window: Option<i64>,
+        jail_period: Option<i64>,
+        unjail_fee: Option<u64>,
+    ) -> Result<()> {
+        // Ensure only authority can update
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.validator_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        let config = &mut ctx.accounts.validator_config;
+        
+        if let Some(window) = jail_window {
+            require!(window > 0, ErrorCode::InvalidJailConfig);
+            config.jail_window = window;
+        }
+        
+        if let Some(period) = jail_period {
+            require!(period >= 0, ErrorCode::InvalidJailConfig);
+            config.jail_period = period;
+        }
+        
+        if let Some(fee) = unjail_fee {
+            config.unjail_fee = fee;
+        }
+        
+        emit!(JailConfigUpdated {
+            validator_config: config.key(),
+            jail_window: config.jail_window,
+            jail_period: config.jail_period,
+            unjail_fee: config.unjail_fee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Jail configuration updated");
+        Ok(())
+    }
+}
+
+/// Scale an amount by reputation, leaving it unchanged at `REPUTATION_NEUTRAL`
+pub fn scale_by_reputation(amount: u64, reputation: u16) -> u64 {
+    (amount as u128 * reputation as u128 / REPUTATION_NEUTRAL as u128) as u64
+}
+
+/// Mint NRAM rewards through nram_token, with the config PDA signing as the
+/// token program's reward minter
+#[allow(clippy::result_large_err, clippy::too_many_arguments)]
+fn mint_rewards<'info>(
+    validator_config: &Account<'info, ValidatorConfig>,
+    token_config: &Account<'info, TokenConfig>,
+    reward_token_mint: &Account<'info, Mint>,
+    destination: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    system_program: &Program<'info, System>,
+    nram_token_program: &Program<'info, NramToken>,
+    amount: u64,
+) -> Result<()> {
+    let config_seeds: &[&[u8]] = &[b"validator-config", &[validator_config.bump]];
+    nram_token::cpi::mint_tokens(
+        CpiContext::new_with_signer(
+            nram_token_program.to_account_info(),
+            nram_token::cpi::accounts::MintTokens {
+                authority: validator_config.to_account_info(),
+                token_config: token_config.to_account_info(),
+                mint: reward_token_mint.to_account_info(),
+                token_account: destination.to_account_info(),
+                token_program: token_program.to_account_info(),
+                system_program: system_program.to_account_info(),
+            },
+            &[config_seeds],
+        ),
+        amount,
+    )
+}
+
+#[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -172,7 +1422,20 @@ pub struct Initialize<'info> {
     pub staking_token_mint: Account<'info, Mint>,
     pub reward_token_mint: Account<'info, Mint>,
     
+    /// Receives slashed stake, owned by the config PDA
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"treasury"],
+        bump,
+        token::mint = staking_token_mint,
+        token::authority = validator_config
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
@@ -198,17 +1461,32 @@ pub struct RegisterValidator<'info> {
     
     #[account(
         mut,
-        constraint = staking_token_account.mint == validator_config.staking_token_mint,
-        constraint = staking_token_account.owner == authority.key()
+        constraint = staking_token_account.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = staking_token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccount
     )]
     pub staking_token_account: Account<'info, TokenAccount>,
     
+    #[account(address = validator_config.staking_token_mint)]
+    pub staking_token_mint: Account<'info, Mint>,
+    
+    /// Escrow for this validator's stake, owned by the validator PDA
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"stake-vault", validator.key().as_ref()],
+        bump,
+        token::mint = staking_token_mint,
+        token::authority = validator
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct SubmitValidation<'info> {
+pub struct CommitValidation<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
@@ -220,27 +1498,51 @@ pub struct SubmitValidation<'info> {
     
     #[account(
         mut,
-        seeds = [b"validator", authority.key().as_ref()],
+        seeds = [b"validator", validator.authority.as_ref()],
         bump = validator.bump,
-        constraint = validator.authority == authority.key()
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
     )]
     pub validator: Account<'info, Validator>,
     
+    /// The memory being validated, owned by the memory_nft program
     #[account(
-        init,
+        seeds = [b"memory", memory.mint.as_ref()],
+        bump = memory.bump,
+        seeds::program = memory_nft::ID
+    )]
+    pub memory: Account<'info, Memory>,
+    
+    /// The token account holding the memory NFT
+    #[account(
+        constraint = memory_token_account.mint == memory.mint @ ErrorCode::InvalidTokenAccount,
+        constraint = memory_token_account.amount == 1 @ ErrorCode::InvalidTokenAccount
+    )]
+    pub memory_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
         payer = authority,
         space = 8 + Validation::LEN,
-        seeds = [b"validation", validator.key().as_ref(), &validator.validations_performed.to_le_bytes()],
+        seeds = [b"validation", memory.key().as_ref(), validator.key().as_ref()],
         bump
     )]
     pub validation: Account<'info, Validation>,
     
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ValidationRound::LEN,
+        seeds = [b"validation-round", memory.key().as_ref()],
+        bump
+    )]
+    pub validation_round: Account<'info, ValidationRound>,
+    
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ProcessValidation<'info> {
-    #[account(mut)]
+pub struct RevealValidation<'info> {
     pub authority: Signer<'info>,
     
     #[account(
@@ -250,39 +1552,87 @@ pub struct ProcessValidation<'info> {
     pub validator_config: Account<'info, ValidatorConfig>,
     
     #[account(
-        mut,
-        constraint = validator.validator_config == validator_config.key()
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
     )]
     pub validator: Account<'info, Validator>,
     
     #[account(
         mut,
-        constraint = validation.validator == validator.key(),
-        constraint = validation.validator_config == validator_config.key(),
-        constraint = !validation.is_processed
+        seeds = [b"validation", validation.memory_id.as_ref(), validation.validator.as_ref()],
+        bump = validation.bump,
+        constraint = validation.validator == validator.key() @ ErrorCode::ValidatorMismatch,
+        constraint = validation.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
     )]
     pub validation: Account<'info, Validation>,
     
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"validation-round", validation.memory_id.as_ref()],
+        bump = validation_round.bump,
+        constraint = validation_round.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validation_round: Account<'info, ValidationRound>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateValidatorConfig<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+pub struct FinalizeRound<'info> {
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
     
     #[account(
         mut,
+        seeds = [b"validation-round", validation_round.memory_id.as_ref()],
+        bump = validation_round.bump,
+        constraint = validation_round.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validation_round: Account<'info, ValidationRound>,
+}
+
+#[derive(Accounts)]
+pub struct PenalizeUnrevealed<'info> {
+    #[account(
         seeds = [b"validator-config"],
         bump = validator_config.bump
     )]
     pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        seeds = [b"validation", validation.memory_id.as_ref(), validation.validator.as_ref()],
+        bump = validation.bump,
+        constraint = validation.validator == validator.key() @ ErrorCode::ValidatorMismatch,
+        constraint = validation.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig,
+        constraint = !validation.is_revealed @ ErrorCode::AlreadyRevealed,
+        constraint = !validation.is_processed @ ErrorCode::AlreadyProcessed
+    )]
+    pub validation: Account<'info, Validation>,
+    
+    #[account(
+        mut,
+        seeds = [b"validation-round", validation.memory_id.as_ref()],
+        bump = validation_round.bump,
+        constraint = validation_round.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validation_round: Account<'info, ValidationRound>,
 }
 
 #[derive(Accounts)]
-pub struct Unstake<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+pub struct ProcessValidation<'info> {
+    pub cranker: Signer<'info>,
     
     #[account(
         seeds = [b"validator-config"],
@@ -292,30 +1642,64 @@ pub struct Unstake<'info> {
     
     #[account(
         mut,
-        seeds = [b"validator", authority.key().as_ref()],
+        seeds = [b"validator", validator.authority.as_ref()],
         bump = validator.bump,
-        constraint = validator.authority == authority.key(),
-        constraint = validator.is_active
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
     )]
     pub validator: Account<'info, Validator>,
     
     #[account(
         mut,
-        constraint = staking_token_account.mint == validator_config.staking_token_mint,
-        constraint = staking_token_account.owner == authority.key()
+        seeds = [b"validation", validation.memory_id.as_ref(), validation.validator.as_ref()],
+        bump = validation.bump,
+        constraint = validation.validator == validator.key() @ ErrorCode::ValidatorMismatch,
+        constraint = validation.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig,
+        constraint = !validation.is_processed @ ErrorCode::AlreadyProcessed,
+        constraint = validation.is_revealed @ ErrorCode::NotRevealed
     )]
-    pub staking_token_account: Account<'info, TokenAccount>,
+    pub validation: Account<'info, Validation>,
+    
+    #[account(
+        mut,
+        seeds = [b"validation-round", validation.memory_id.as_ref()],
+        bump = validation_round.bump,
+        constraint = validation_round.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validation_round: Account<'info, ValidationRound>,
+    
+    /// Receives the crank tip, if the config pays one
+    #[account(
+        mut,
+        constraint = cranker_token_account.mint == validator_config.reward_token_mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub cranker_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = validator_config.reward_token_mint
+    )]
+    pub reward_token_mint: Account<'info, Mint>,
     
+    #[account(
+        mut,
+        seeds = [b"token-config", reward_token_mint.key().as_ref()],
+        bump = token_config.bump,
+        seeds::program = nram_token::ID
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub nram_token_program: Program<'info, NramToken>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimRewards<'info> {
+pub struct SlashValidator<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
     
     #[account(
+        mut,
         seeds = [b"validator-config"],
         bump = validator_config.bump
     )]
@@ -323,83 +1707,1875 @@ pub struct ClaimRewards<'info> {
     
     #[account(
         mut,
-        seeds = [b"validator", authority.key().as_ref()],
+        seeds = [b"validator", validator.authority.as_ref()],
         bump = validator.bump,
-        constraint = validator.authority == authority.key(),
-        constraint = validator.rewards_earned > 0
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
     )]
     pub validator: Account<'info, Validator>,
     
     #[account(
         mut,
-        constraint = reward_token_account.mint == validator_config.reward_token_mint,
-        constraint = reward_token_account.owner == authority.key()
+        seeds = [b"stake-vault", validator.key().as_ref()],
+        bump
     )]
-    pub reward_token_account: Account<'info, TokenAccount>,
+    pub stake_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SlashRecord::LEN,
+        seeds = [b"slash", validator.key().as_ref(), &validator.slash_count.to_le_bytes()],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct ValidatorConfig {
-    pub authority: Pubkey,
-    pub staking_token_mint: Pubkey,
-    pub reward_token_mint: Pubkey,
-    pub validator_count: u64,
-    pub min_stake_amount: u64,
-    pub reward_per_validation: u64,
-    pub validation_threshold: u8,
-    pub bump: u8,
-}
-
-impl ValidatorConfig {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1;
-}
-
-#[account]
-pub struct Validator {
-    pub authority: Pubkey,
-    pub stake_amount: u64,
-    pub validations_performed: u64,
-    pub rewards_earned: u64,
-    pub is_active: bool,
-    pub last_validation: i64,
-    pub validator_config: Pubkey,
-    pub bump: u8,
-}
-
-impl Validator {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 1 + 8 + 32 + 1;
-}
-
-#[account]
-pub struct Validation {
-    pub validator: Pubkey,
-    pub memory_id: Pubkey,
-    pub is_valid: bool,
-    pub quality_score: u8,
-    pub timestamp: i64,
-    pub is_processed: bool,
-    pub validator_config: Pubkey,
-    pub bump: u8,
-}
-
-impl Validation {
-    pub const LEN: usize = 32 + 32 + 1 + 1 + 8 + 1 + 32 + 1;
-}
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Not authorized to perform this action")]
-    NotAuthorized,
+#[derive(Accounts)]
+pub struct ReverseSlash<'info> {
+    pub authority: Signer<'info>,
     
-    #[msg("Insufficient stake amount")]
-    InsufficientStake,
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
     
-    #[msg("Validator is not active")]
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", validator.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"slash", validator.key().as_ref(), &slash_record.index.to_le_bytes()],
+        bump = slash_record.bump,
+        constraint = slash_record.validator == validator.key() @ ErrorCode::ValidatorMismatch,
+        constraint = slash_record.status == SlashStatus::Pending @ ErrorCode::SlashNotPending
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSlash<'info> {
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        address = validator_config.staking_token_mint
+    )]
+    pub staking_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"slash", slash_record.validator.as_ref(), &slash_record.index.to_le_bytes()],
+        bump = slash_record.bump,
+        constraint = slash_record.status == SlashStatus::Pending @ ErrorCode::SlashNotPending
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeMemoryQuality<'info> {
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        seeds = [b"validation-round", validation_round.memory_id.as_ref()],
+        bump = validation_round.bump,
+        constraint = validation_round.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig,
+        constraint = validation_round.memory_id == memory.key() @ ErrorCode::MemoryMismatch
+    )]
+    pub validation_round: Account<'info, ValidationRound>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", memory.mint.as_ref()],
+        bump = memory.bump,
+        seeds::program = memory_nft::ID
+    )]
+    pub memory: Account<'info, Memory>,
+    
+    pub memory_nft_program: Program<'info, MemoryNft>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateValidatorConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        constraint = staking_token_account.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = staking_token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub staking_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", validator.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReactivateValidator<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        constraint = staking_token_account.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = staking_token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub staking_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", validator.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    
+    /// Receives the unjail fee when a jailed validator comes back
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig,
+        constraint = validator.rewards_earned > 0 @ ErrorCode::NoRewardsToClaim
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        constraint = reward_token_account.mint == validator_config.reward_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = reward_token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub reward_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = validator_config.reward_token_mint
+    )]
+    pub reward_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"token-config", reward_token_mint.key().as_ref()],
+        bump = token_config.bump,
+        seeds::program = nram_token::ID
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub nram_token_program: Program<'info, NramToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCommission<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+}
+
+#[derive(Accounts)]
+pub struct Delegate<'info> {
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        space = 8 + Delegation::LEN,
+        seeds = [b"delegation", validator.key().as_ref(), delegator.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(
+        mut,
+        constraint = delegator_token_account.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = delegator_token_account.owner == delegator.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub delegator_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = validator_config.staking_token_mint)]
+    pub staking_token_mint: Account<'info, Mint>,
+    
+    /// Pool holding everything delegated to this validator, owned by the validator PDA
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        seeds = [b"delegation-vault", validator.key().as_ref()],
+        bump,
+        token::mint = staking_token_mint,
+        token::authority = validator
+    )]
+    pub delegation_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Undelegate<'info> {
+    pub delegator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        seeds = [b"delegation", validator.key().as_ref(), delegator.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawDelegation<'info> {
+    pub delegator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        seeds = [b"delegation", validator.key().as_ref(), delegator.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(
+        mut,
+        constraint = delegator_token_account.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = delegator_token_account.owner == delegator.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub delegator_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"delegation-vault", validator.key().as_ref()],
+        bump
+    )]
+    pub delegation_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDelegationRewards<'info> {
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+    
+    #[account(
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    #[account(
+        mut,
+        seeds = [b"delegation", validator.key().as_ref(), delegator.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(
+        mut,
+        constraint = reward_token_account.mint == validator_config.reward_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = reward_token_account.owner == delegator.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub reward_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = validator_config.reward_token_mint
+    )]
+    pub reward_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"token-config", reward_token_mint.key().as_ref()],
+        bump = token_config.bump,
+        seeds::program = nram_token::ID
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub nram_token_program: Program<'info, NramToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JailValidator<'info> {
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+}
+
+#[derive(Accounts)]
+pub struct Unjail<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator-config"],
+        bump = validator_config.bump
+    )]
+    pub validator_config: Account<'info, ValidatorConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"validator", validator.authority.as_ref()],
+        bump = validator.bump,
+        constraint = validator.authority == authority.key() @ ErrorCode::NotAuthorized,
+        constraint = validator.validator_config == validator_config.key() @ ErrorCode::InvalidValidatorConfig
+    )]
+    pub validator: Account<'info, Validator>,
+    
+    /// Pays the unjail fee, if there is one
+    #[account(
+        mut,
+        constraint = staking_token_account.mint == validator_config.staking_token_mint @ ErrorCode::InvalidTokenAccount,
+        constraint = staking_token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub staking_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+/// Maps an averaged 0-10 validator quality score onto the 1-4 memory quality tiers
+pub fn quality_tier(quality_score: u8) -> u8 {
+    match quality_score {
+        0..=2 => 1,
+        3..=5 => 2,
+        6..=8 => 3,
+        _ => 4,
+    }
+}
+
+#[account]
+pub struct ValidatorConfig {
+    pub authority: Pubkey,
+    pub staking_token_mint: Pubkey,
+    pub reward_token_mint: Pubkey,
+    /// Active validators; unbonding and jailed validators are not counted
+    pub validator_count: u64,
+    pub min_stake_amount: u64,
+    pub reward_per_validation: u64,
+    /// Reward tokens paid to whoever processes a validation
+    pub crank_tip: u64,
+    pub validation_threshold: u8,
+    /// Slots a validator waits between requesting to unstake and withdrawing
+    pub unbonding_slots: u64,
+    /// Seconds validators have to commit once a round opens
+    pub commit_period: i64,
+    /// Seconds validators have to reveal once the commit phase closes
+    pub reveal_period: i64,
+    /// Largest distance from the consensus quality score that still counts as agreeing
+    pub max_quality_deviation: u8,
+    /// Disagreeing votes in a row that make a validator slashable
+    pub max_deviations: u8,
+    /// Share of stake taken per slash, in basis points
+    pub slash_bps: u16,
+    /// Burn slashed stake once the appeal window ends instead of keeping it in the treasury
+    pub burn_slashed: bool,
+    /// Seconds the authority has to reverse a slash
+    pub appeal_window: i64,
+    /// Slashed stake in the treasury that is still open to appeal
+    pub pending_slashed: u64,
+    /// Weight round votes by stake instead of counting one per validator
+    pub stake_weighted: bool,
+    /// Most weight a single stake-weighted vote can carry, or 0 for no cap
+    pub max_vote_weight: u64,
+    /// Seconds without a vote for a validator's distance from neutral reputation to halve, or 0 for no decay
+    pub reputation_half_life: i64,
+    /// Reputation gained for a vote that matches consensus
+    pub reputation_gain: u16,
+    /// Reputation lost for a vote that doesn't, or one that is never revealed
+    pub reputation_penalty: u16,
+    /// Seconds without a vote after which anyone can jail a validator
+    pub jail_window: i64,
+    /// Seconds a jailed validator waits before it can unjail
+    pub jail_period: i64,
+    /// Staking tokens paid into the treasury to unjail
+    pub unjail_fee: u64,
+    pub bump: u8,
+}
+
+impl ValidatorConfig {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 1 + 1 + 2 + 1 + 8 + 8 + 1 + 8 + 8 + 2 + 2 + 8 + 8 + 8 + 1;
+    
+    /// Weight a validator's vote carries in a round. Each vote starts from
+    /// `REPUTATION_NEUTRAL`, or from its capped stake when stake weighted,
+    /// and is scaled by the validator's reputation.
+    pub fn vote_weight(&self, stake_amount: u64, reputation: u16) -> u64 {
+        let base = if !self.stake_weighted {
+            REPUTATION_NEUTRAL as u64
+        } else if self.max_vote_weight > 0 {
+            stake_amount.min(self.max_vote_weight)
+        } else {
+            stake_amount
+        };
+        scale_by_reputation(base, reputation)
+    }
+}
+
+#[account]
+pub struct Validator {
+    pub authority: Pubkey,
+    pub stake_amount: u64,
+    pub validations_performed: u64,
+    pub rewards_earned: u64,
+    pub is_active: bool,
+    pub last_validation: i64,
+    /// When the validator last became active, so new validators aren't jailed for not having voted yet
+    pub active_since: i64,
+    /// When a jailed validator can unjail, zero if not jailed
+    pub jailed_until: i64,
+    /// Slot after which an unstaking validator can withdraw, zero if not unbonding
+    pub unbonding_until: u64,
+    /// Processed votes in a row that disagreed with consensus
+    pub deviations: u8,
+    pub slash_count: u64,
+    /// Committed votes not yet processed or penalized
+    pub open_validations: u32,
+    /// Reputation as of `reputation_updated_at`, out of `REPUTATION_MAX`. It drifts
+    /// back towards `REPUTATION_NEUTRAL` the longer the validator goes without
+    /// voting; `current_reputation` applies that decay.
+    pub reputation: u16,
+    /// When `reputation` was last settled, which its decay counts from
+    pub reputation_updated_at: i64,
+    /// Tokens other holders have delegated to this validator
+    pub delegated_stake: u64,
+    /// Share of the delegators' rewards the validator keeps, in basis points
+    pub commission_bps: u16,
+    /// Rewards accrued per delegated token, scaled by `REWARD_PER_SHARE_PRECISION`
+    pub reward_per_share: u128,
+    pub validator_config: Pubkey,
+    pub bump: u8,
+}
+
+impl Validator {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 8 + 4 + 2 + 8 + 8 + 2 + 16 + 32 + 1;
+    
+    pub fn is_jailed(&self) -> bool {
+        self.jailed_until != 0
+    }
+    
+    /// Own stake plus everything delegated to the validator
+    pub fn total_stake(&self) -> u64 {
+        self.stake_amount.saturating_add(self.delegated_stake)
+    }
+    
+    /// Split a reward pro rata between the validator's own stake and its
+    /// delegators, with the commission on the delegators' part going to the
+    /// validator. Returns the validator's share; the delegators' share accrues
+    /// to `reward_per_share`.
+    pub fn distribute_reward(&mut self, amount: u64) -> u64 {
+        let total_stake = self.total_stake();
+        if self.delegated_stake == 0 || total_stake == 0 {
+            return amount;
+        }
+        let delegated_part = (amount as u128 * self.delegated_stake as u128 / total_stake as u128) as u64;
+        let commission = (delegated_part as u128 * self.commission_bps as u128 / 10_000) as u64;
+        let delegator_share = delegated_part - commission;
+        self.reward_per_share = self
+            .reward_per_share
+            .checked_add(delegator_share as u128 * REWARD_PER_SHARE_PRECISION / self.delegated_stake as u128)
+            .unwrap();
+        amount - delegator_share
+    }
+    
+    /// Reputation with the decay since it was last settled applied
+    pub fn current_reputation(&self, half_life: i64, now: i64) -> u16 {
+        let elapsed = now.saturating_sub(self.reputation_updated_at);
+        if half_life <= 0 || elapsed < half_life {
+            return self.reputation;
+        }
+        let halvings = elapsed / half_life;
+        if halvings >= 16 {
+            return REPUTATION_NEUTRAL;
+        }
+        let distance = self.reputation as i32 - REPUTATION_NEUTRAL as i32;
+        (REPUTATION_NEUTRAL as i32 + distance / (1 << halvings)) as u16
+    }
+    
+    /// Fold the decay so far into the stored reputation
+    pub fn settle_reputation(&mut self, half_life: i64, now: i64) {
+        self.reputation = self.current_reputation(half_life, now);
+        self.reputation_updated_at = now;
+    }
+    
+    /// Move reputation up for a vote that matched consensus, down otherwise.
+    /// Decay is settled first so the change applies to the current value.
+    pub fn adjust_reputation(&mut self, agrees: bool, config: &ValidatorConfig, now: i64) {
+        self.settle_reputation(config.reputation_half_life, now);
+        self.reputation = if agrees {
+            self.reputation.saturating_add(config.reputation_gain).min(REPUTATION_MAX)
+        } else {
+            self.reputation.saturating_sub(config.reputation_penalty)
+        };
+    }
+}
+
+#[account]
+pub struct Validation {
+    pub validator: Pubkey,
+    pub memory_id: Pubkey,
+    /// Index of the validation round the vote was cast in
+    pub round: u32,
+    /// `vote_commitment` of the vote, sealed until the reveal phase
+    pub commitment: [u8; 32],
+    pub is_revealed: bool,
+    pub is_valid: bool,
+    pub quality_score: u8,
+    /// Weight the vote carries in its round, fixed on commit
+    pub weight: u64,
+    pub timestamp: i64,
+    pub is_processed: bool,
+    pub validator_config: Pubkey,
+    pub bump: u8,
+}
+
+impl Validation {
+    pub const LEN: usize = 32 + 32 + 4 + 32 + 1 + 1 + 1 + 8 + 8 + 1 + 32 + 1;
+}
+
+/// Hash a validator commits to before revealing its vote on a memory
+pub fn vote_commitment(
+    validator: &Pubkey,
+    memory_id: &Pubkey,
+    is_valid: bool,
+    quality_score: u8,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    hashv(&[
+        validator.as_ref(),
+        memory_id.as_ref(),
+        &[is_valid as u8],
+        &[quality_score],
+        salt,
+    ])
+    .to_bytes()
+}
+
+/// Tally of every vote cast on one memory. Votes are committed, then revealed once
+/// the commit deadline passes.
+#[account]
+pub struct ValidationRound {
+    pub memory_id: Pubkey,
+    pub validator_config: Pubkey,
+    /// Index of the current round, bumped each time an expired round reopens
+    pub round: u32,
+    pub commits: u32,
+    /// Commitments not yet processed or penalized
+    pub unsettled: u32,
+    pub commit_deadline: i64,
+    pub reveal_deadline: i64,
+    /// Revealed votes
+    pub valid_votes: u32,
+    pub invalid_votes: u32,
+    /// Revealed vote weight, each vote adding `vote_weight(total_stake, reputation)`
+    /// as of its commit
+    pub valid_weight: u64,
+    pub invalid_weight: u64,
+    /// Weight of the valid votes cast for each quality score from 0 to 10
+    pub quality_weights: [u64; 11],
+    pub status: RoundStatus,
+    /// Median quality score of the valid votes, set when the round is accepted
+    pub quality_score: u8,
+    pub finalized_at: i64,
+    pub bump: u8,
+}
+
+impl ValidationRound {
+    pub const LEN: usize = 32 + 32 + 4 + 4 + 4 + 8 + 8 + 4 + 4 + 8 + 8 + 11 * 8 + 1 + 1 + 8 + 1;
+    
+    /// Start a commit phase with empty tallies
+    pub fn open(&mut self, config: &ValidatorConfig, timestamp: i64) {
+        self.commits = 0;
+        self.commit_deadline = timestamp.checked_add(config.commit_period).unwrap();
+        self.reveal_deadline = self.commit_deadline.checked_add(config.reveal_period).unwrap();
+        self.valid_votes = 0;
+        self.invalid_votes = 0;
+        self.valid_weight = 0;
+        self.invalid_weight = 0;
+        self.quality_weights = [0; 11];
+        self.status = RoundStatus::Commit;
+        self.quality_score = 0;
+        self.finalized_at = 0;
+    }
+    
+    pub fn is_finalized(&self) -> bool {
+        matches!(
+            self.status,
+            RoundStatus::Accepted | RoundStatus::Rejected | RoundStatus::Expired
+        )
+    }
+    
+    /// Settle the round from its revealed votes. Too few reveals leave it
+    /// expired; otherwise a majority of valid vote weight accepts the memory
+    /// at the weighted median quality score.
+    pub fn finalize(&mut self, validation_threshold: u8, timestamp: i64) {
+        let total_weight = self.valid_weight as u128 + self.invalid_weight as u128;
+        if self.total_votes() < validation_threshold as u32 {
+            self.status = RoundStatus::Expired;
+        } else if self.valid_weight as u128 * 2 > total_weight {
+            self.status = RoundStatus::Accepted;
+            self.quality_score = self.median_quality();
+        } else {
+            self.status = RoundStatus::Rejected;
+        }
+        self.finalized_at = timestamp;
+    }
+    
+    pub fn total_votes(&self) -> u32 {
+        self.valid_votes + self.invalid_votes
+    }
+    
+    /// Lower weighted median of the quality scores cast with valid votes
+    pub fn median_quality(&self) -> u8 {
+        let target = self.valid_weight.saturating_sub(1) / 2;
+        let mut seen = 0;
+        for (score, weight) in self.quality_weights.iter().enumerate() {
+            seen += weight;
+            if seen > target {
+                return score as u8;
+            }
+        }
+        0
+    }
+    
+    /// Whether a vote matches this round's outcome, allowing the quality
+    /// score to sit within `max_quality_deviation` of the consensus score
+    pub fn agrees_with(&self, is_valid: bool, quality_score: u8, max_quality_deviation: u8) -> bool {
+        match self.status {
+            RoundStatus::Accepted => {
+                is_valid && quality_score.abs_diff(self.quality_score) <= max_quality_deviation
+            }
+            RoundStatus::Rejected => !is_valid,
+            RoundStatus::Commit | RoundStatus::Reveal | RoundStatus::Expired => false,
+        }
+    }
+}
+
+/// A slash taken from a validator's stake, reversible by the authority until
+/// the appeal deadline
+#[account]
+pub struct SlashRecord {
+    pub validator: Pubkey,
+    /// The validator's `slash_count` when this slash was applied
+    pub index: u64,
+    pub amount: u64,
+    /// Whether the tokens are burned rather than kept when the slash is finalized
+    pub burn: bool,
+    pub slashed_at: i64,
+    pub appeal_deadline: i64,
+    pub status: SlashStatus,
+    pub bump: u8,
+}
+
+impl SlashRecord {
+    pub const LEN: usize = 32 + 8 + 8 + 1 + 8 + 8 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlashStatus {
+    Pending,
+    Reversed,
+    Finalized,
+}
+
+/// Receipt for tokens a holder has delegated to a validator
+#[account]
+pub struct Delegation {
+    pub delegator: Pubkey,
+    pub validator: Pubkey,
+    /// Tokens currently delegated and earning rewards
+    pub amount: u64,
+    /// `amount` times the validator's `reward_per_share` when last settled
+    pub reward_debt: u128,
+    /// Rewards settled but not yet claimed
+    pub pending_rewards: u64,
+    /// Undelegated tokens waiting for `unbonding_until`
+    pub unbonding_amount: u64,
+    pub unbonding_until: u64,
+    pub bump: u8,
+}
+
+impl Delegation {
+    pub const LEN: usize = 32 + 32 + 8 + 16 + 8 + 8 + 8 + 1;
+    
+    /// Move rewards accrued since the last settlement into `pending_rewards`
+    pub fn settle(&mut self, reward_per_share: u128) {
+        let accrued = (self.amount as u128 * reward_per_share / REWARD_PER_SHARE_PRECISION)
+            .saturating_sub(self.reward_debt);
+        self.pending_rewards = self.pending_rewards.checked_add(accrued as u64).unwrap();
+    }
+    
+    pub fn reset_reward_debt(&mut self, reward_per_share: u128) {
+        self.reward_debt = self.amount as u128 * reward_per_share / REWARD_PER_SHARE_PRECISION;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoundStatus {
+    Commit,
+    Reveal,
+    Accepted,
+    Rejected,
+    /// Closed with fewer reveals than the validation threshold
+    Expired,
+}
+
+#[event]
+pub struct ValidatorConfigInitialized {
+    pub validator_config: Pubkey,
+    pub authority: Pubkey,
+    pub staking_token_mint: Pubkey,
+    pub reward_token_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorConfigUpdated {
+    pub validator_config: Pubkey,
+    pub min_stake_amount: u64,
+    pub reward_per_validation: u64,
+    pub validation_threshold: u8,
+    pub unbonding_slots: u64,
+    pub commit_period: i64,
+    pub reveal_period: i64,
+    pub crank_tip: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SlashingConfigUpdated {
+    pub validator_config: Pubkey,
+    pub max_quality_deviation: u8,
+    pub max_deviations: u8,
+    pub slash_bps: u16,
+    pub burn_slashed: bool,
+    pub appeal_window: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationConfigUpdated {
+    pub validator_config: Pubkey,
+    pub reputation_half_life: i64,
+    pub reputation_gain: u16,
+    pub reputation_penalty: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConsensusConfigUpdated {
+    pub validator_config: Pubkey,
+    pub stake_weighted: bool,
+    pub max_vote_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorRegistered {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorUnstaked {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub stake_amount: u64,
+    pub unbonding_until: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorReactivated {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidationCommitted {
+    pub validation: Pubkey,
+    pub validator: Pubkey,
+    pub memory_id: Pubkey,
+    pub commitment: [u8; 32],
+    pub reveal_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidationRevealed {
+    pub validation: Pubkey,
+    pub validator: Pubkey,
+    pub memory_id: Pubkey,
+    pub is_valid: bool,
+    pub quality_score: u8,
+    pub weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnrevealedValidationPenalized {
+    pub validation: Pubkey,
+    pub validator: Pubkey,
+    pub memory_id: Pubkey,
+    pub deviations: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidationProcessed {
+    pub validation: Pubkey,
+    pub validator: Pubkey,
+    pub memory_id: Pubkey,
+    pub reward_amount: u64,
+    pub delegator_rewards: u64,
+    pub reputation: u16,
+    pub cranker: Pubkey,
+    pub tip: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorSlashed {
+    pub validator: Pubkey,
+    pub slash_record: Pubkey,
+    pub amount: u64,
+    pub remaining_stake: u64,
+    pub appeal_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SlashReversed {
+    pub validator: Pubkey,
+    pub slash_record: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SlashFinalized {
+    pub validator: Pubkey,
+    pub slash_record: Pubkey,
+    pub amount: u64,
+    pub burned: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub validator_config: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidationRoundFinalized {
+    pub validation_round: Pubkey,
+    pub memory_id: Pubkey,
+    pub status: RoundStatus,
+    pub valid_votes: u32,
+    pub invalid_votes: u32,
+    pub valid_weight: u64,
+    pub invalid_weight: u64,
+    pub quality_score: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryQualityFinalized {
+    pub memory: Pubkey,
+    pub validation_round: Pubkey,
+    pub quality_score: u8,
+    pub quality: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorJailed {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub last_validation: i64,
+    pub jailed_until: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorUnjailed {
+    pub validator: Pubkey,
+    pub authority: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JailConfigUpdated {
+    pub validator_config: Pubkey,
+    pub jail_window: i64,
+    pub jail_period: i64,
+    pub unjail_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommissionUpdated {
+    pub validator: Pubkey,
+    pub commission_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeDelegated {
+    pub delegation: Pubkey,
+    pub validator: Pubkey,
+    pub delegator: Pubkey,
+    pub amount: u64,
+    pub delegated_stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeUndelegated {
+    pub delegation: Pubkey,
+    pub validator: Pubkey,
+    pub delegator: Pubkey,
+    pub amount: u64,
+    pub unbonding_until: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationWithdrawn {
+    pub delegation: Pubkey,
+    pub validator: Pubkey,
+    pub delegator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationRewardsClaimed {
+    pub delegation: Pubkey,
+    pub validator: Pubkey,
+    pub delegator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Not authorized to perform this action")]
+    NotAuthorized,
+    
+    #[msg("Insufficient stake amount")]
+    InsufficientStake,
+    
+    #[msg("Validator is not active")]
     ValidatorInactive,
     
+    #[msg("Validator is already active")]
+    ValidatorActive,
+    
+    #[msg("Validator has not requested to unstake")]
+    NotUnbonding,
+    
+    #[msg("Unbonding period has not ended yet")]
+    UnbondingNotComplete,
+    
+    #[msg("Validator has votes that are not processed yet")]
+    OpenValidations,
+    
+    #[msg("Validator must be slashed before it can withdraw")]
+    SlashPending,
+    
     #[msg("Invalid quality score (must be 0-10)")]
     InvalidQualityScore,
+    
+    #[msg("Validator has already voted on this memory")]
+    DuplicateValidation,
+    
+    #[msg("Validators cannot validate memories they own")]
+    CannotValidateOwnMemory,
+    
+    #[msg("Validators did not accept this memory")]
+    MemoryRejected,
+    
+    #[msg("Validation round is already finalized")]
+    RoundFinalized,
+    
+    #[msg("Validation threshold must be at least 1")]
+    InvalidThreshold,
+    
+    #[msg("Validation round has not been finalized yet")]
+    RoundNotFinalized,
+    
+    #[msg("Every vote in the expired round must be processed before it reopens")]
+    RoundNotSettled,
+    
+    #[msg("Commit phase for this round is over")]
+    CommitPeriodOver,
+    
+    #[msg("Reveal phase for this round has not started")]
+    RevealNotOpen,
+    
+    #[msg("Reveal phase for this round is over")]
+    RevealPeriodOver,
+    
+    #[msg("Reveal phase for this round is still running")]
+    RevealPeriodNotOver,
+    
+    #[msg("Validation has already been revealed")]
+    AlreadyRevealed,
+    
+    #[msg("Revealed vote does not match the commitment")]
+    InvalidReveal,
+    
+    #[msg("Validation has not been revealed")]
+    NotRevealed,
+    
+    #[msg("Commit and reveal periods must be positive")]
+    InvalidPeriod,
+    
+    #[msg("Validator has not disagreed with consensus often enough to be slashed")]
+    SlashThresholdNotReached,
+    
+    #[msg("Slash rate cannot exceed 10000 basis points")]
+    InvalidSlashRate,
+    
+    #[msg("Appeal window cannot be negative")]
+    InvalidAppealWindow,
+    
+    #[msg("Slash is no longer pending")]
+    SlashNotPending,
+    
+    #[msg("Appeal window for this slash has closed")]
+    AppealWindowClosed,
+    
+    #[msg("Appeal window for this slash is still open")]
+    AppealWindowOpen,
+    
+    #[msg("Treasury withdrawals must be positive and leave pending slashes in place")]
+    InsufficientTreasuryBalance,
+    
+    #[msg("Account belongs to a different validator config")]
+    InvalidValidatorConfig,
+    
+    #[msg("Account belongs to a different validator")]
+    ValidatorMismatch,
+    
+    #[msg("Account belongs to a different memory")]
+    MemoryMismatch,
+    
+    #[msg("Validation has already been processed")]
+    AlreadyProcessed,
+    
+    #[msg("Token account does not match the expected mint or owner")]
+    InvalidTokenAccount,
+    
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+    
+    #[msg("Reputation half-life cannot be negative and changes cannot exceed the reputation range")]
+    InvalidReputationConfig,
+    
+    #[msg("Commission cannot exceed 10000 basis points")]
+    InvalidCommission,
+    
+    #[msg("Delegation amount must be positive and no more than is delegated")]
+    InvalidDelegationAmount,
+    
+    #[msg("Undelegated tokens are still waiting to be withdrawn")]
+    AlreadyUnbonding,
+    
+    #[msg("Validator is not jailed")]
+    NotJailed,
+    
+    #[msg("Jail period has not ended yet")]
+    JailPeriodNotOver,
+    
+    #[msg("Validator has voted within the jail window")]
+    ValidatorNotIdle,
+    
+    #[msg("Jail window must be positive and the jail period cannot be negative")]
+    InvalidJailConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_nft_trusts_this_program() {
+        assert_eq!(memory_nft::MEMORY_VALIDATOR_PROGRAM_ID, ID);
+    }
+
+    #[test]
+    fn validator_config_len_matches_serialized_size() {
+        let validator_config = ValidatorConfig {
+            authority: Pubkey::new_unique(),
+            staking_token_mint: Pubkey::new_unique(),
+            reward_token_mint: Pubkey::new_unique(),
+            validator_count: u64::MAX,
+            min_stake_amount: u64::MAX,
+            reward_per_validation: u64::MAX,
+            crank_tip: u64::MAX,
+            validation_threshold: u8::MAX,
+            unbonding_slots: u64::MAX,
+            commit_period: i64::MAX,
+            reveal_period: i64::MAX,
+            max_quality_deviation: u8::MAX,
+            max_deviations: u8::MAX,
+            slash_bps: u16::MAX,
+            burn_slashed: true,
+            appeal_window: i64::MAX,
+            pending_slashed: u64::MAX,
+            stake_weighted: true,
+            max_vote_weight: u64::MAX,
+            reputation_half_life: i64::MAX,
+            reputation_gain: u16::MAX,
+            reputation_penalty: u16::MAX,
+            jail_window: i64::MAX,
+            jail_period: i64::MAX,
+            unjail_fee: u64::MAX,
+            bump: 255,
+        };
+        assert_eq!(validator_config.try_to_vec().unwrap().len(), ValidatorConfig::LEN);
+    }
+
+    #[test]
+    fn validator_len_matches_serialized_size() {
+        let validator = Validator {
+            authority: Pubkey::new_unique(),
+            stake_amount: u64::MAX,
+            validations_performed: u64::MAX,
+            rewards_earned: u64::MAX,
+            is_active: true,
+            last_validation: i64::MAX,
+            active_since: i64::MAX,
+            jailed_until: i64::MAX,
+            unbonding_until: u64::MAX,
+            deviations: u8::MAX,
+            slash_count: u64::MAX,
+            open_validations: u32::MAX,
+            reputation: u16::MAX,
+            reputation_updated_at: i64::MAX,
+            delegated_stake: u64::MAX,
+            commission_bps: u16::MAX,
+            reward_per_share: u128::MAX,
+            validator_config: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(validator.try_to_vec().unwrap().len(), Validator::LEN);
+    }
+
+    #[test]
+    fn validation_round_len_matches_serialized_size() {
+        let validation_round = ValidationRound {
+            memory_id: Pubkey::new_unique(),
+            validator_config: Pubkey::new_unique(),
+            round: u32::MAX,
+            commits: u32::MAX,
+            unsettled: u32::MAX,
+            commit_deadline: i64::MAX,
+            reveal_deadline: i64::MAX,
+            valid_votes: u32::MAX,
+            invalid_votes: u32::MAX,
+            valid_weight: u64::MAX,
+            invalid_weight: u64::MAX,
+            quality_weights: [u64::MAX; 11],
+            status: RoundStatus::Accepted,
+            quality_score: 10,
+            finalized_at: i64::MAX,
+            bump: 255,
+        };
+        assert_eq!(validation_round.try_to_vec().unwrap().len(), ValidationRound::LEN);
+    }
+
+    #[test]
+    fn median_quality_ignores_outliers() {
+        let mut quality_weights = [0; 11];
+        quality_weights[0] = 1;
+        quality_weights[7] = 2;
+        quality_weights[10] = 1;
+        let validation_round = ValidationRound {
+            memory_id: Pubkey::new_unique(),
+            validator_config: Pubkey::new_unique(),
+            round: 0,
+            commits: u32::MAX,
+            unsettled: 0,
+            commit_deadline: i64::MAX,
+            reveal_deadline: i64::MAX,
+            valid_votes: 4,
+            invalid_votes: 0,
+            valid_weight: 4,
+            invalid_weight: 0,
+            quality_weights,
+            status: RoundStatus::Reveal,
+            quality_score: 0,
+            finalized_at: 0,
+            bump: 255,
+        };
+        assert_eq!(validation_round.median_quality(), 7);
+    }
+
+    #[test]
+    fn validation_len_matches_serialized_size() {
+        let validation = Validation {
+            validator: Pubkey::new_unique(),
+            memory_id: Pubkey::new_unique(),
+            round: u32::MAX,
+            commitment: [7; 32],
+            is_revealed: true,
+            is_valid: true,
+            quality_score: 10,
+            weight: u64::MAX,
+            timestamp: i64::MAX,
+            is_processed: true,
+            validator_config: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(validation.try_to_vec().unwrap().len(), Validation::LEN);
+    }
+
+    #[test]
+    fn vote_commitment_binds_every_field() {
+        let validator = Pubkey::new_unique();
+        let memory_id = Pubkey::new_unique();
+        let salt = [9; 32];
+        let commitment = vote_commitment(&validator, &memory_id, true, 7, &salt);
+        assert_eq!(commitment, vote_commitment(&validator, &memory_id, true, 7, &salt));
+        assert_ne!(commitment, vote_commitment(&Pubkey::new_unique(), &memory_id, true, 7, &salt));
+        assert_ne!(commitment, vote_commitment(&validator, &Pubkey::new_unique(), true, 7, &salt));
+        assert_ne!(commitment, vote_commitment(&validator, &memory_id, false, 7, &salt));
+        assert_ne!(commitment, vote_commitment(&validator, &memory_id, true, 8, &salt));
+        assert_ne!(commitment, vote_commitment(&validator, &memory_id, true, 7, &[8; 32]));
+    }
+
+    #[test]
+    fn slash_record_len_matches_serialized_size() {
+        let slash_record = SlashRecord {
+            validator: Pubkey::new_unique(),
+            index: u64::MAX,
+            amount: u64::MAX,
+            burn: true,
+            slashed_at: i64::MAX,
+            appeal_deadline: i64::MAX,
+            status: SlashStatus::Finalized,
+            bump: 255,
+        };
+        assert_eq!(slash_record.try_to_vec().unwrap().len(), SlashRecord::LEN);
+    }
+
+    #[test]
+    fn votes_agree_within_the_quality_tolerance() {
+        let mut validation_round = ValidationRound {
+            memory_id: Pubkey::new_unique(),
+            validator_config: Pubkey::new_unique(),
+            round: 0,
+            commits: u32::MAX,
+            unsettled: 0,
+            commit_deadline: i64::MAX,
+            reveal_deadline: i64::MAX,
+            valid_votes: 3,
+            invalid_votes: 0,
+            valid_weight: 3,
+            invalid_weight: 0,
+            quality_weights: [0; 11],
+            status: RoundStatus::Accepted,
+            quality_score: 6,
+            finalized_at: 0,
+            bump: 255,
+        };
+        assert!(validation_round.agrees_with(true, 8, 2));
+        assert!(!validation_round.agrees_with(true, 9, 2));
+        assert!(!validation_round.agrees_with(false, 6, 2));
+        
+        validation_round.status = RoundStatus::Rejected;
+        assert!(validation_round.agrees_with(false, 0, 2));
+        assert!(!validation_round.agrees_with(true, 6, 2));
+    }
+
+    #[test]
+    fn vote_weight_follows_the_consensus_mode() {
+        let mut validator_config = ValidatorConfig {
+            authority: Pubkey::new_unique(),
+            staking_token_mint: Pubkey::new_unique(),
+            reward_token_mint: Pubkey::new_unique(),
+            validator_count: 0,
+            min_stake_amount: 1000,
+            reward_per_validation: 10,
+            crank_tip: 0,
+            validation_threshold: 3,
+            unbonding_slots: 0,
+            commit_period: 0,
+            reveal_period: 0,
+            max_quality_deviation: 3,
+            max_deviations: 3,
+            slash_bps: 500,
+            burn_slashed: false,
+            appeal_window: 0,
+            pending_slashed: 0,
+            stake_weighted: false,
+            max_vote_weight: 0,
+            reputation_half_life: 0,
+            reputation_gain: 100,
+            reputation_penalty: 200,
+            jail_window: 0,
+            jail_period: 0,
+            unjail_fee: 0,
+            bump: 255,
+        };
+        assert_eq!(validator_config.vote_weight(50_000, REPUTATION_NEUTRAL), REPUTATION_NEUTRAL as u64);
+        
+        validator_config.stake_weighted = true;
+        assert_eq!(validator_config.vote_weight(50_000, REPUTATION_NEUTRAL), 50_000);
+        
+        validator_config.max_vote_weight = 10_000;
+        assert_eq!(validator_config.vote_weight(50_000, REPUTATION_NEUTRAL), 10_000);
+        assert_eq!(validator_config.vote_weight(1000, REPUTATION_NEUTRAL), 1000);
+        
+        // Reputation scales the weight either way
+        assert_eq!(validator_config.vote_weight(1000, REPUTATION_MAX), 2000);
+        assert_eq!(validator_config.vote_weight(1000, 2_500), 500);
+    }
+
+    #[test]
+    fn stake_weight_outvotes_a_larger_number_of_small_validators() {
+        let mut quality_weights = [0; 11];
+        quality_weights[2] = 3000;
+        quality_weights[9] = 5000;
+        let mut validation_round = ValidationRound {
+            memory_id: Pubkey::new_unique(),
+            validator_config: Pubkey::new_unique(),
+            round: 0,
+            commits: 4,
+            unsettled: 0,
+            commit_deadline: 0,
+            reveal_deadline: 0,
+            valid_votes: 4,
+            invalid_votes: 0,
+            valid_weight: 8000,
+            invalid_weight: 0,
+            quality_weights,
+            status: RoundStatus::Reveal,
+            quality_score: 0,
+            finalized_at: 0,
+            bump: 255,
+        };
+        validation_round.finalize(3, 1);
+        assert_eq!(validation_round.status, RoundStatus::Accepted);
+        assert_eq!(validation_round.quality_score, 9);
+        
+        // Three small invalid votes lose to one large valid vote
+        validation_round.status = RoundStatus::Reveal;
+        validation_round.valid_votes = 1;
+        validation_round.invalid_votes = 3;
+        validation_round.invalid_weight = 3000;
+        validation_round.finalize(3, 1);
+        assert_eq!(validation_round.status, RoundStatus::Accepted);
+    }
+
+    #[test]
+    fn reputation_decays_towards_neutral_without_votes() {
+        let day = 24 * 60 * 60;
+        let mut validator = Validator {
+            authority: Pubkey::new_unique(),
+            stake_amount: 1000,
+            validations_performed: 1,
+            rewards_earned: 0,
+            is_active: true,
+            last_validation: 0,
+            active_since: 0,
+            jailed_until: 0,
+            unbonding_until: 0,
+            deviations: 0,
+            slash_count: 0,
+            open_validations: 0,
+            reputation: 9_000,
+            reputation_updated_at: 0,
+            delegated_stake: 0,
+            commission_bps: 0,
+            reward_per_share: 0,
+            validator_config: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(validator.current_reputation(30 * day, 29 * day), 9_000);
+        assert_eq!(validator.current_reputation(30 * day, 30 * day), 7_000);
+        assert_eq!(validator.current_reputation(30 * day, 60 * day), 6_000);
+        assert_eq!(validator.current_reputation(30 * day, 10_000 * day), REPUTATION_NEUTRAL);
+        assert_eq!(validator.current_reputation(0, 10_000 * day), 9_000);
+        
+        // Low reputation recovers towards neutral the same way
+        validator.reputation = 1_000;
+        assert_eq!(validator.current_reputation(30 * day, 30 * day), 3_000);
+    }
+
+    #[test]
+    fn reputation_stays_in_range() {
+        let mut validator = Validator {
+            authority: Pubkey::new_unique(),
+            stake_amount: 1000,
+            validations_performed: 1,
+            rewards_earned: 0,
+            is_active: true,
+            last_validation: 0,
+            active_since: 0,
+            jailed_until: 0,
+            unbonding_until: 0,
+            deviations: 0,
+            slash_count: 0,
+            open_validations: 0,
+            reputation: REPUTATION_MAX - 50,
+            reputation_updated_at: 0,
+            delegated_stake: 0,
+            commission_bps: 0,
+            reward_per_share: 0,
+            validator_config: Pubkey::new_unique(),
+            bump: 255,
+        };
+        let validator_config = ValidatorConfig {
+            authority: Pubkey::new_unique(),
+            staking_token_mint: Pubkey::new_unique(),
+            reward_token_mint: Pubkey::new_unique(),
+            validator_count: 0,
+            min_stake_amount: 1000,
+            reward_per_validation: 10,
+            crank_tip: 0,
+            validation_threshold: 3,
+            unbonding_slots: 0,
+            commit_period: 0,
+            reveal_period: 0,
+            max_quality_deviation: 3,
+            max_deviations: 3,
+            slash_bps: 500,
+            burn_slashed: false,
+            appeal_window: 0,
+            pending_slashed: 0,
+            stake_weighted: false,
+            max_vote_weight: 0,
+            reputation_half_life: 0,
+            reputation_gain: 100,
+            reputation_penalty: 200,
+            jail_window: 0,
+            jail_period: 0,
+            unjail_fee: 0,
+            bump: 255,
+        };
+        validator.adjust_reputation(true, &validator_config, 0);
+        assert_eq!(validator.reputation, REPUTATION_MAX);
+        
+        validator.reputation = 150;
+        validator.adjust_reputation(false, &validator_config, 0);
+        assert_eq!(validator.reputation, 0);
+        
+        assert_eq!(scale_by_reputation(10, REPUTATION_NEUTRAL), 10);
+        assert_eq!(scale_by_reputation(10, REPUTATION_MAX), 20);
+        assert_eq!(scale_by_reputation(10, 0), 0);
+    }
+
+    #[test]
+    fn adjust_reputation_settles_decay_first() {
+        let day = 24 * 60 * 60;
+        let mut validator = Validator {
+            authority: Pubkey::new_unique(),
+            stake_amount: 1000,
+            validations_performed: 1,
+            rewards_earned: 0,
+            is_active: true,
+            last_validation: 0,
+            active_since: 0,
+            jailed_until: 0,
+            unbonding_until: 0,
+            deviations: 0,
+            slash_count: 0,
+            open_validations: 0,
+            reputation: 9_000,
+            reputation_updated_at: 0,
+            delegated_stake: 0,
+            commission_bps: 0,
+            reward_per_share: 0,
+            validator_config: Pubkey::new_unique(),
+            bump: 255,
+        };
+        let validator_config = ValidatorConfig {
+            authority: Pubkey::new_unique(),
+            staking_token_mint: Pubkey::new_unique(),
+            reward_token_mint: Pubkey::new_unique(),
+            validator_count: 0,
+            min_stake_amount: 1000,
+            reward_per_validation: 10,
+            crank_tip: 0,
+            validation_threshold: 3,
+            unbonding_slots: 0,
+            commit_period: 0,
+            reveal_period: 0,
+            max_quality_deviation: 3,
+            max_deviations: 3,
+            slash_bps: 500,
+            burn_slashed: false,
+            appeal_window: 0,
+            pending_slashed: 0,
+            stake_weighted: false,
+            max_vote_weight: 0,
+            reputation_half_life: 30 * day,
+            reputation_gain: 100,
+            reputation_penalty: 200,
+            jail_window: 0,
+            jail_period: 0,
+            unjail_fee: 0,
+            bump: 255,
+        };
+        
+        // A month without votes halves the distance to neutral before the gain
+        validator.adjust_reputation(true, &validator_config, 30 * day);
+        assert_eq!(validator.reputation, 7_100);
+        assert_eq!(validator.reputation_updated_at, 30 * day);
+        
+        // Decay restarts from the adjustment rather than the last vote, so it
+        // isn't applied twice
+        assert_eq!(validator.current_reputation(30 * day, 59 * day), 7_100);
+        assert_eq!(validator.current_reputation(30 * day, 60 * day), 6_050);
+    }
+
+    #[test]
+    fn delegation_len_matches_serialized_size() {
+        let delegation = Delegation {
+            delegator: Pubkey::new_unique(),
+            validator: Pubkey::new_unique(),
+            amount: u64::MAX,
+            reward_debt: u128::MAX,
+            pending_rewards: u64::MAX,
+            unbonding_amount: u64::MAX,
+            unbonding_until: u64::MAX,
+            bump: 255,
+        };
+        assert_eq!(delegation.try_to_vec().unwrap().len(), Delegation::LEN);
+    }
+
+    #[test]
+    fn rewards_split_pro_rata_with_commission() {
+        let mut validator = Validator {
+            authority: Pubkey::new_unique(),
+            stake_amount: 1000,
+            validations_performed: 0,
+            rewards_earned: 0,
+            is_active: true,
+            last_validation: 0,
+            active_since: 0,
+            jailed_until: 0,
+            unbonding_until: 0,
+            deviations: 0,
+            slash_count: 0,
+            open_validations: 0,
+            reputation: REPUTATION_NEUTRAL,
+            reputation_updated_at: 0,
+            delegated_stake: 3000,
+            commission_bps: 1000,
+            reward_per_share: 0,
+            validator_config: Pubkey::new_unique(),
+            bump: 255,
+        };
+        let mut early = Delegation {
+            delegator: Pubkey::new_unique(),
+            validator: Pubkey::new_unique(),
+            amount: 3000,
+            reward_debt: 0,
+            pending_rewards: 0,
+            unbonding_amount: 0,
+            unbonding_until: 0,
+            bump: 255,
+        };
+        
+        // Delegators hold 3/4 of the stake: 300 of 400, less 10% commission
+        assert_eq!(validator.distribute_reward(400), 130);
+        early.settle(validator.reward_per_share);
+        early.reset_reward_debt(validator.reward_per_share);
+        assert_eq!(early.pending_rewards, 270);
+        
+        // A later delegator doesn't share in rewards from before it joined
+        let mut late = Delegation { amount: 1000, ..early.clone() };
+        late.pending_rewards = 0;
+        late.reset_reward_debt(validator.reward_per_share);
+        validator.delegated_stake += 1000;
+        late.settle(validator.reward_per_share);
+        assert_eq!(late.pending_rewards, 0);
+        
+        // Without delegators the validator keeps everything
+        validator.delegated_stake = 0;
+        assert_eq!(validator.distribute_reward(400), 400);
+    }
+
+    #[test]
+    fn quality_tier_covers_the_score_range() {
+        assert_eq!(quality_tier(0), 1);
+        assert_eq!(quality_tier(5), 2);
+        assert_eq!(quality_tier(8), 3);
+        assert_eq!(quality_tier(10), 4);
+    }
 } 
\ No newline at end of file
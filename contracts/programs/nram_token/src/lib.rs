@@ -1,12 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
 
-declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+declare_id!("6YQyRRpH2CCoQwutJXMH4nHTUgLRZ1GcrthUXsDXtMjb");
 
 #[program]
 pub mod nram_token {
     use super::*;
     
+    #[allow(clippy::result_large_err)]
     pub fn initialize(
         ctx: Context<Initialize>,
         total_supply: u64,
@@ -18,19 +20,31 @@ pub mod nram_token {
         token_config.circulating_supply = 0;
         token_config.staking_enabled = false;
         token_config.governance_enabled = false;
+        token_config.reward_minter = Pubkey::default();
         token_config.bump = *ctx.bumps.get("token_config").unwrap();
         
+        emit!(TokenInitialized {
+            token_config: token_config.key(),
+            mint: token_config.mint,
+            authority: token_config.authority,
+            total_supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("NRAM token initialized with total supply: {}", total_supply);
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
     pub fn mint_tokens(
         ctx: Context<MintTokens>,
         amount: u64,
     ) -> Result<()> {
-        // Ensure only authority can mint
+        // Ensure only authority or the reward minter can mint
+        let authority = ctx.accounts.authority.key();
         require!(
-            ctx.accounts.authority.key() == ctx.accounts.token_config.authority,
+            authority == ctx.accounts.token_config.authority
+                || authority == ctx.accounts.token_config.reward_minter,
             ErrorCode::NotAuthorized
         );
         
@@ -44,27 +58,74 @@ pub mod nram_token {
             ErrorCode::ExceedsTotalSupply
         );
         
+        // Mint to the destination, signed by the config PDA that holds the
+        // mint authority
+        let mint_key = ctx.accounts.mint.key();
+        let config_seeds: &[&[u8]] = &[b"token-config", mint_key.as_ref(), &[token_config.bump]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.token_account.to_account_info(),
+                    authority: token_config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            amount,
+        )?;
+        
         // Update circulating supply
         token_config.circulating_supply = new_circulating;
         
+        emit!(TokensMinted {
+            mint: token_config.mint,
+            authority: ctx.accounts.authority.key(),
+            amount,
+            circulating_supply: new_circulating,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Minted {} NRAM tokens", amount);
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
     pub fn burn_tokens(
         ctx: Context<BurnTokens>,
         amount: u64,
     ) -> Result<()> {
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.token_account.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        
         // Update circulating supply
         let token_config = &mut ctx.accounts.token_config;
         token_config.circulating_supply = token_config.circulating_supply
             .checked_sub(amount)
             .ok_or(ErrorCode::SupplyUnderflow)?;
+        
+        emit!(TokensBurned {
+            mint: token_config.mint,
+            owner: ctx.accounts.authority.key(),
+            amount,
+            circulating_supply: token_config.circulating_supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
             
         msg!("Burned {} NRAM tokens", amount);
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
     pub fn enable_staking(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
         // Ensure only authority can update config
         require!(
@@ -75,10 +136,17 @@ pub mod nram_token {
         let token_config = &mut ctx.accounts.token_config;
         token_config.staking_enabled = enabled;
         
+        emit!(StakingToggled {
+            mint: token_config.mint,
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Staking {}abled for NRAM token", if enabled { "en" } else { "dis" });
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
     pub fn enable_governance(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
         // Ensure only authority can update config
         require!(
@@ -89,10 +157,38 @@ pub mod nram_token {
         let token_config = &mut ctx.accounts.token_config;
         token_config.governance_enabled = enabled;
         
+        emit!(GovernanceToggled {
+            mint: token_config.mint,
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Governance {}abled for NRAM token", if enabled { "en" } else { "dis" });
         Ok(())
     }
     
+    #[allow(clippy::result_large_err)]
+    pub fn set_reward_minter(ctx: Context<UpdateConfig>, reward_minter: Pubkey) -> Result<()> {
+        // Ensure only authority can update config
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.token_config.authority,
+            ErrorCode::NotAuthorized
+        );
+        
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.reward_minter = reward_minter;
+        
+        emit!(RewardMinterSet {
+            mint: token_config.mint,
+            reward_minter,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Reward minter set to: {}", reward_minter);
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
     pub fn transfer_authority(ctx: Context<TransferAuthority>) -> Result<()> {
         // Ensure only current authority can transfer
         require!(
@@ -101,8 +197,16 @@ pub mod nram_token {
         );
         
         let token_config = &mut ctx.accounts.token_config;
+        let previous_authority = token_config.authority;
         token_config.authority = ctx.accounts.new_authority.key();
         
+        emit!(AuthorityTransferred {
+            mint: token_config.mint,
+            previous_authority,
+            new_authority: token_config.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         msg!("Authority transferred to: {}", token_config.authority);
         Ok(())
     }
@@ -122,6 +226,10 @@ pub struct Initialize<'info> {
     )]
     pub token_config: Account<'info, TokenConfig>,
     
+    /// The config PDA must hold the mint authority so it can sign mints
+    #[account(
+        constraint = mint.mint_authority == COption::Some(token_config.key()) @ ErrorCode::InvalidMintAuthority
+    )]
     pub mint: Account<'info, Mint>,
     
     pub system_program: Program<'info, System>,
@@ -129,18 +237,25 @@ pub struct Initialize<'info> {
 
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
-    #[account(mut)]
     pub authority: Signer<'info>,
     
     #[account(
         mut,
-        seeds = [b"token-config", mint.key().as_ref()],
-        bump = token_config.bump
+        seeds = [b"token-config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        has_one = mint @ ErrorCode::InvalidMint
     )]
     pub token_config: Account<'info, TokenConfig>,
     
+    #[account(mut)]
     pub mint: Account<'info, Mint>,
     
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ ErrorCode::InvalidTokenAccount
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -152,8 +267,9 @@ pub struct BurnTokens<'info> {
     
     #[account(
         mut,
-        seeds = [b"token-config", mint.key().as_ref()],
-        bump = token_config.bump
+        seeds = [b"token-config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        has_one = mint @ ErrorCode::InvalidMint
     )]
     pub token_config: Account<'info, TokenConfig>,
     
@@ -162,7 +278,8 @@ pub struct BurnTokens<'info> {
     
     #[account(
         mut,
-        constraint = token_account.owner == authority.key()
+        constraint = token_account.mint == mint.key() @ ErrorCode::InvalidTokenAccount,
+        constraint = token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccount
     )]
     pub token_account: Account<'info, TokenAccount>,
     
@@ -177,8 +294,9 @@ pub struct UpdateConfig<'info> {
     
     #[account(
         mut,
-        seeds = [b"token-config", mint.key().as_ref()],
-        bump = token_config.bump
+        seeds = [b"token-config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        has_one = mint @ ErrorCode::InvalidMint
     )]
     pub token_config: Account<'info, TokenConfig>,
     
@@ -195,8 +313,9 @@ pub struct TransferAuthority<'info> {
     
     #[account(
         mut,
-        seeds = [b"token-config", mint.key().as_ref()],
-        bump = token_config.bump
+        seeds = [b"token-config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        has_one = mint @ ErrorCode::InvalidMint
     )]
     pub token_config: Account<'info, TokenConfig>,
     
@@ -211,11 +330,69 @@ pub struct TokenConfig {
     pub circulating_supply: u64,
     pub staking_enabled: bool,
     pub governance_enabled: bool,
+    /// Program signer allowed to mint rewards besides the authority
+    pub reward_minter: Pubkey,
     pub bump: u8,
 }
 
 impl TokenConfig {
-    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1 + 1;
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 1 + 32 + 1;
+}
+
+#[event]
+pub struct TokenInitialized {
+    pub token_config: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub total_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensMinted {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub circulating_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensBurned {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub circulating_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakingToggled {
+    pub mint: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GovernanceToggled {
+    pub mint: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardMinterSet {
+    pub mint: Pubkey,
+    pub reward_minter: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub mint: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
 }
 
 #[error_code]
@@ -231,4 +408,33 @@ pub enum ErrorCode {
     
     #[msg("Mint would exceed total supply")]
     ExceedsTotalSupply,
-} 
\ No newline at end of file
+    
+    #[msg("Token config must be the mint authority")]
+    InvalidMintAuthority,
+    
+    #[msg("Token config belongs to a different mint")]
+    InvalidMint,
+    
+    #[msg("Token account does not match the mint or signer")]
+    InvalidTokenAccount,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_config_len_matches_serialized_size() {
+        let token_config = TokenConfig {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            total_supply: u64::MAX,
+            circulating_supply: u64::MAX,
+            staking_enabled: true,
+            governance_enabled: true,
+            reward_minter: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(token_config.try_to_vec().unwrap().len(), TokenConfig::LEN);
+    }
+}
@@ -14,8 +14,37 @@ import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
   createAssociatedTokenAccountInstruction,
   getAssociatedTokenAddress,
+  getAccount,
+  createTransferInstruction,
 } from "@solana/spl-token";
 
+import { createHash } from "crypto";
+
+// Default 5% royalty on secondary sales
+const SELLER_FEE_BASIS_POINTS = 500;
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+// Derive the Metaplex metadata and master edition PDAs for a mint
+const findMetadataAddresses = async (mint: PublicKey) => {
+  const [metadata] = await PublicKey.findProgramAddress(
+    [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+  const [masterEdition] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("metadata"),
+      TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+      Buffer.from("edition"),
+    ],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+  return { metadata, masterEdition };
+};
+
 describe("memory_nft", () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
@@ -30,15 +59,46 @@ describe("memory_nft", () => {
   let mintKeypair: Keypair;
   let memoryPDA: PublicKey;
   let memoryBump: number;
+  let newOwnerKeypair: Keypair;
+  let fingerprintRecordPDA: PublicKey;
+  
+  // Neural fingerprints are stored on-chain as SHA-256 digests of the neural data
+  const fingerprintOf = (neuralData: string) => [
+    ...createHash("sha256").update(neuralData).digest(),
+  ];
+  
+  // Derive the fingerprint registry PDA for a fingerprint digest
+  const findFingerprintRecord = async (fingerprint: number[]) => {
+    const [record] = await PublicKey.findProgramAddress(
+      [Buffer.from("fingerprint"), Buffer.from(fingerprint)],
+      program.programId
+    );
+    return record;
+  };
 
   before(async () => {
     // Find the program derived address for memory data
     [memoryDataPDA, memoryDataBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("memory_data")],
+      [Buffer.from("memory-data")],
       program.programId
     );
   });
 
+  it("Lists every event in the IDL", async () => {
+    const events = program.idl.events.map((event) => event.name);
+    expect(events).to.include.members([
+      "MemoryProgramInitialized",
+      "MemoryMinted",
+      "MemoryTransferred",
+      "TransferabilityChanged",
+      "MemoryMetadataUpdated",
+      "MemoryQualityUpdated",
+      "MemoryCollectionCreated",
+      "MemoryCollectionUpdated",
+      "MemoryBurned",
+    ]);
+  });
+
   it("Initializes the memory NFT program", async () => {
     const tx = await program.methods
       .initialize()
@@ -76,14 +136,17 @@ describe("memory_nft", () => {
       wallet.publicKey
     );
     
+    const { metadata, masterEdition } = await findMetadataAddresses(mintKeypair.publicKey);
+    
     // Prepare test memory data
     const uri = "https://arweave.net/memory-data-uri";
     const name = "Test Memory";
     const symbol = "TMEM";
-    const memoryType = "cognitive";
+    const memoryType = { cognitive: {} };
     const memoryQuality = 3;
-    const neuralFingerprint = "unique-neural-fingerprint-hash";
-    const timestamp = Math.floor(Date.now() / 1000);
+    const neuralFingerprint = fingerprintOf("unique-neural-fingerprint-data");
+    fingerprintRecordPDA = await findFingerprintRecord(neuralFingerprint);
+    const capturedAt = Math.floor(Date.now() / 1000) - 3600;
     
     const tx = await program.methods
       .mintMemory(
@@ -93,14 +156,21 @@ describe("memory_nft", () => {
         memoryType,
         memoryQuality,
         neuralFingerprint,
-        new BN(timestamp)
+        new BN(capturedAt),
+        false,
+        SELLER_FEE_BASIS_POINTS,
+        []
       )
       .accounts({
         authority: wallet.publicKey,
         memoryData: memoryDataPDA,
         memory: memoryPDA,
+        fingerprintRecord: fingerprintRecordPDA,
         mint: mintKeypair.publicKey,
         tokenAccount: associatedTokenAddress,
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -111,16 +181,36 @@ describe("memory_nft", () => {
     
     console.log("Mint memory transaction signature", tx);
     
+    // The minter holds exactly one token
+    const tokenAccount = await getAccount(provider.connection, associatedTokenAddress);
+    expect(tokenAccount.amount.toString()).to.equal("1");
+    
+    // Metadata and master edition were created by the token metadata program
+    const metadataInfo = await provider.connection.getAccountInfo(metadata);
+    expect(metadataInfo.owner.toString()).to.equal(TOKEN_METADATA_PROGRAM_ID.toString());
+    const masterEditionInfo = await provider.connection.getAccountInfo(masterEdition);
+    expect(masterEditionInfo.owner.toString()).to.equal(TOKEN_METADATA_PROGRAM_ID.toString());
+    
     // Fetch the memory account
     const memory = await program.account.memory.fetch(memoryPDA);
     
     // Verify memory was initialized correctly
     expect(memory.owner.toString()).to.equal(wallet.publicKey.toString());
     expect(memory.mint.toString()).to.equal(mintKeypair.publicKey.toString());
-    expect(memory.memoryType).to.equal(memoryType);
+    expect(memory.memoryType).to.deep.equal(memoryType);
     expect(memory.quality).to.equal(memoryQuality);
-    expect(memory.neuralFingerprint).to.equal(neuralFingerprint);
-    expect(memory.createdAt.toNumber()).to.equal(timestamp);
+    expect(memory.neuralFingerprint).to.deep.equal(neuralFingerprint);
+    
+    // The fingerprint registry points back at this mint
+    const fingerprintRecord = await program.account.fingerprintRecord.fetch(fingerprintRecordPDA);
+    expect(fingerprintRecord.mint.toString()).to.equal(mintKeypair.publicKey.toString());
+    expect(fingerprintRecord.memory.toString()).to.equal(memoryPDA.toString());
+    expect(memory.capturedAt.toNumber()).to.equal(capturedAt);
+    
+    // The mint time comes from the cluster clock, not the client
+    const slot = await provider.connection.getSlot();
+    const blockTime = await provider.connection.getBlockTime(slot);
+    expect(memory.createdAt.toNumber()).to.be.closeTo(blockTime, 60);
     expect(memory.isTransferable).to.equal(true);
     expect(memory.bump).to.equal(memoryBump);
     
@@ -129,13 +219,129 @@ describe("memory_nft", () => {
     expect(memoryData.memoryCount.toNumber()).to.equal(1);
   });
 
+  it("Rejects a second mint of the same neural fingerprint", async () => {
+    const duplicateMint = Keypair.generate();
+    const [duplicateMemory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory"), duplicateMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const tokenAccount = await getAssociatedTokenAddress(
+      duplicateMint.publicKey,
+      wallet.publicKey
+    );
+    const { metadata, masterEdition } = await findMetadataAddresses(duplicateMint.publicKey);
+    
+    try {
+      await program.methods
+        .mintMemory(
+          "https://arweave.net/memory-data-uri",
+          "Duplicate Memory",
+          "DUP",
+          { cognitive: {} },
+          3,
+          fingerprintOf("unique-neural-fingerprint-data"),
+          new BN(Math.floor(Date.now() / 1000)),
+          false,
+          SELLER_FEE_BASIS_POINTS,
+          []
+        )
+        .accounts({
+          authority: wallet.publicKey,
+          memoryData: memoryDataPDA,
+          memory: duplicateMemory,
+          fingerprintRecord: fingerprintRecordPDA,
+          mint: duplicateMint.publicKey,
+          tokenAccount,
+          metadata,
+          masterEdition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([duplicateMint])
+        .rpc();
+      
+      expect.fail("Minting a duplicate fingerprint should have failed");
+    } catch (error) {
+      // Verify it's the correct error
+      expect(error.toString()).to.include("DuplicateFingerprint");
+    }
+  });
+
+  it("Rejects creator shares that do not add up to 100", async () => {
+    const royaltyMint = Keypair.generate();
+    const [royaltyMemory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory"), royaltyMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const tokenAccount = await getAssociatedTokenAddress(
+      royaltyMint.publicKey,
+      wallet.publicKey
+    );
+    const { metadata, masterEdition } = await findMetadataAddresses(royaltyMint.publicKey);
+    const fingerprint = fingerprintOf("royalty-test-neural-data");
+    
+    try {
+      await program.methods
+        .mintMemory(
+          "https://arweave.net/memory-data-uri",
+          "Royalty Memory",
+          "ROY",
+          { cultural: {} },
+          2,
+          fingerprint,
+          new BN(Math.floor(Date.now() / 1000)),
+          false,
+          SELLER_FEE_BASIS_POINTS,
+          [
+            { address: wallet.publicKey, share: 50 },
+            { address: Keypair.generate().publicKey, share: 40 },
+          ]
+        )
+        .accounts({
+          authority: wallet.publicKey,
+          memoryData: memoryDataPDA,
+          memory: royaltyMemory,
+          fingerprintRecord: await findFingerprintRecord(fingerprint),
+          mint: royaltyMint.publicKey,
+          tokenAccount,
+          metadata,
+          masterEdition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([royaltyMint])
+        .rpc();
+      
+      expect.fail("Minting with invalid creator shares should have failed");
+    } catch (error) {
+      // Verify it's the correct error
+      expect(error.toString()).to.include("InvalidCreatorShares");
+    }
+  });
+
   it("Toggles memory transferability", async () => {
+    const tokenAccount = await getAssociatedTokenAddress(
+      mintKeypair.publicKey,
+      wallet.publicKey
+    );
+    const { masterEdition } = await findMetadataAddresses(mintKeypair.publicKey);
+    
     const tx = await program.methods
       .toggleTransferable(false)
       .accounts({
         authority: wallet.publicKey,
         memory: memoryPDA,
         mint: mintKeypair.publicKey,
+        tokenAccount,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
     
@@ -144,8 +350,10 @@ describe("memory_nft", () => {
     // Fetch the memory account
     let memory = await program.account.memory.fetch(memoryPDA);
     
-    // Verify transferability was toggled off
+    // Verify transferability was toggled off and the token is frozen
     expect(memory.isTransferable).to.equal(false);
+    let token = await getAccount(provider.connection, tokenAccount);
+    expect(token.isFrozen).to.equal(true);
     
     // Toggle it back on
     const tx2 = await program.methods
@@ -154,6 +362,10 @@ describe("memory_nft", () => {
         authority: wallet.publicKey,
         memory: memoryPDA,
         mint: mintKeypair.publicKey,
+        tokenAccount,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
     
@@ -162,13 +374,16 @@ describe("memory_nft", () => {
     // Fetch the memory account again
     memory = await program.account.memory.fetch(memoryPDA);
     
-    // Verify transferability was toggled back on
+    // Verify transferability was toggled back on and the token is thawed
     expect(memory.isTransferable).to.equal(true);
+    token = await getAccount(provider.connection, tokenAccount);
+    expect(token.isFrozen).to.equal(false);
+    expect(token.delegate).to.be.null;
   });
 
   it("Transfers a memory NFT to a new owner", async () => {
     // Generate a new keypair to act as the new owner
-    const newOwnerKeypair = Keypair.generate();
+    newOwnerKeypair = Keypair.generate();
     
     // Airdrop SOL to the new owner for transaction fees
     const airdropTx = await provider.connection.requestAirdrop(
@@ -212,9 +427,55 @@ describe("memory_nft", () => {
     
     // Verify memory owner was updated
     expect(memory.owner.toString()).to.equal(newOwnerKeypair.publicKey.toString());
+    
+    // Verify the token itself moved to the new owner
+    const fromAccount = await getAccount(provider.connection, currentOwnerTokenAccount);
+    const toAccount = await getAccount(provider.connection, newOwnerTokenAccount);
+    expect(fromAccount.amount.toString()).to.equal("0");
+    expect(toAccount.amount.toString()).to.equal("1");
+  });
+
+  it("Syncs the memory owner after a transfer outside the program", async () => {
+    const newOwnerTokenAccount = await getAssociatedTokenAddress(
+      mintKeypair.publicKey,
+      newOwnerKeypair.publicKey
+    );
+    const walletTokenAccount = await getAssociatedTokenAddress(
+      mintKeypair.publicKey,
+      wallet.publicKey
+    );
+    
+    // Move the token back with a plain SPL transfer
+    const tx = new Transaction();
+    tx.add(
+      createTransferInstruction(
+        newOwnerTokenAccount,
+        walletTokenAccount,
+        newOwnerKeypair.publicKey,
+        1
+      )
+    );
+    await sendAndConfirmTransaction(provider.connection, tx, [newOwnerKeypair]);
+    
+    // The memory account still points at the previous holder
+    let memory = await program.account.memory.fetch(memoryPDA);
+    expect(memory.owner.toString()).to.equal(newOwnerKeypair.publicKey.toString());
+    
+    // Anyone can re-sync it from the token holder
+    await program.methods
+      .syncOwner()
+      .accounts({
+        memory: memoryPDA,
+        mint: mintKeypair.publicKey,
+        tokenAccount: walletTokenAccount,
+      })
+      .rpc();
+    
+    memory = await program.account.memory.fetch(memoryPDA);
+    expect(memory.owner.toString()).to.equal(wallet.publicKey.toString());
   });
 
-  it("Rejects transfer of a non-transferable memory", async () => {
+  it("Mints a soulbound memory that cannot be moved", async () => {
     // Create a new mint for this test
     const newMintKeypair = Keypair.generate();
     
@@ -230,24 +491,32 @@ describe("memory_nft", () => {
       wallet.publicKey
     );
     
-    // Mint a new non-transferable memory
+    const { metadata, masterEdition } = await findMetadataAddresses(newMintKeypair.publicKey);
+    
+    // Mint a new soulbound memory
     await program.methods
       .mintMemory(
-        "Non-transferable Memory",
-        "NTMEM",
         "https://arweave.net/memory-data-uri",
-        "emotional",
-        "common",
-        "0xabcdef1234567890",
-        new BN(Math.floor(Date.now() / 1000))
+        "Soulbound Memory",
+        "SBMEM",
+        { emotional: {} },
+        2,
+        fingerprintOf("0xabcdef1234567890"),
+        new BN(Math.floor(Date.now() / 1000)),
+        true,
+        SELLER_FEE_BASIS_POINTS,
+        []
       )
       .accounts({
-        memory: memoryPda,
+        authority: wallet.publicKey,
         memoryData: memoryDataPDA,
+        memory: memoryPda,
+        fingerprintRecord: await findFingerprintRecord(fingerprintOf("0xabcdef1234567890")),
         mint: newMintKeypair.publicKey,
         tokenAccount: tokenAccount,
-        authority: wallet.publicKey,
-        payer: wallet.publicKey,
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -256,21 +525,16 @@ describe("memory_nft", () => {
       .signers([newMintKeypair])
       .rpc();
     
-    // Set transferable to false
-    await program.methods
-      .setTransferable(false)
-      .accounts({
-        memory: memoryPda,
-        owner: wallet.publicKey,
-      })
-      .rpc();
+    const memory = await program.account.memory.fetch(memoryPda);
+    expect(memory.isTransferable).to.equal(false);
+    expect(memory.isSoulbound).to.equal(true);
+    
+    // The token is frozen in the minter's wallet
+    const token = await getAccount(provider.connection, tokenAccount);
+    expect(token.isFrozen).to.equal(true);
     
     // Create new recipient keypair
     const recipient = Keypair.generate();
-    await provider.connection.requestAirdrop(
-      recipient.publicKey,
-      1 * anchor.web3.LAMPORTS_PER_SOL
-    );
     
     // Create recipient token account
     const recipientTokenAccount = await getAssociatedTokenAddress(
@@ -278,8 +542,8 @@ describe("memory_nft", () => {
       recipient.publicKey
     );
     
-    const tx = new Transaction();
-    tx.add(
+    const createTx = new Transaction();
+    createTx.add(
       createAssociatedTokenAccountInstruction(
         wallet.publicKey,
         recipientTokenAccount,
@@ -287,53 +551,346 @@ describe("memory_nft", () => {
         newMintKeypair.publicKey
       )
     );
-    await sendAndConfirmTransaction(provider.connection, tx, [wallet.payer]);
+    await sendAndConfirmTransaction(provider.connection, createTx, [wallet.payer]);
+    
+    // A plain SPL transfer should fail on the frozen account
+    try {
+      const transferTx = new Transaction();
+      transferTx.add(
+        createTransferInstruction(
+          tokenAccount,
+          recipientTokenAccount,
+          wallet.publicKey,
+          1
+        )
+      );
+      await sendAndConfirmTransaction(provider.connection, transferTx, [wallet.payer]);
+      
+      // If we reach here, the transfer did not fail
+      expect.fail("SPL transfer of a soulbound memory should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("0x11");
+    }
     
-    // Try to transfer - should fail
+    // Transferring through the program should fail as well
     try {
       await program.methods
         .transferMemory()
         .accounts({
+          authority: wallet.publicKey,
+          newOwner: recipient.publicKey,
           memory: memoryPda,
           mint: newMintKeypair.publicKey,
           fromTokenAccount: tokenAccount,
           toTokenAccount: recipientTokenAccount,
-          fromOwner: wallet.publicKey,
-          toOwner: recipient.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
         .rpc();
       
-      // If we reach here, the transfer did not fail
       expect.fail("Transfer of non-transferable memory should have failed");
     } catch (error) {
       // Verify it's the correct error
-      expect(error.toString()).to.include("Memory is not transferable");
+      expect(error.toString()).to.include("This memory NFT is not transferable");
+    }
+    
+    // The owner cannot make it transferable later
+    try {
+      await program.methods
+        .toggleTransferable(true)
+        .accounts({
+          authority: wallet.publicKey,
+          memory: memoryPda,
+          mint: newMintKeypair.publicKey,
+          tokenAccount,
+          masterEdition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      
+      expect.fail("Making a soulbound memory transferable should have failed");
+    } catch (error) {
+      // Verify it's the correct error
+      expect(error.toString()).to.include("Soulbound");
     }
   });
 
-  it("Updates memory URI", async () => {
+  it("Adds and removes a memory in a creator collection", async () => {
+    const collectionMint = Keypair.generate();
+    const [memoryCollection] = await PublicKey.findProgramAddress(
+      [Buffer.from("collection"), collectionMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const collectionTokenAccount = await getAssociatedTokenAddress(
+      collectionMint.publicKey,
+      wallet.publicKey
+    );
+    const {
+      metadata: collectionMetadata,
+      masterEdition: collectionMasterEdition,
+    } = await findMetadataAddresses(collectionMint.publicKey);
+    
+    // A collection without a memory type belongs to its creator
+    await program.methods
+      .createCollection(
+        "My Memories",
+        "MYMEM",
+        "https://arweave.net/collection-uri",
+        null
+      )
+      .accounts({
+        curator: wallet.publicKey,
+        memoryData: memoryDataPDA,
+        memoryCollection,
+        collectionMint: collectionMint.publicKey,
+        collectionTokenAccount,
+        collectionMetadata,
+        collectionMasterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([collectionMint])
+      .rpc();
+    
+    let collection = await program.account.memoryCollection.fetch(memoryCollection);
+    expect(collection.curator.toString()).to.equal(wallet.publicKey.toString());
+    expect(collection.memoryType).to.be.null;
+    expect(collection.itemCount.toNumber()).to.equal(0);
+    
+    // Mint a memory to put in the collection
+    const itemMint = Keypair.generate();
+    const [itemMemory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory"), itemMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const itemTokenAccount = await getAssociatedTokenAddress(
+      itemMint.publicKey,
+      wallet.publicKey
+    );
+    const { metadata, masterEdition } = await findMetadataAddresses(itemMint.publicKey);
+    const fingerprint = fingerprintOf("collection-item-neural-data");
+    
+    await program.methods
+      .mintMemory(
+        "https://arweave.net/collection-item-uri",
+        "Collected Memory",
+        "CMEM",
+        { cultural: {} },
+        4,
+        fingerprint,
+        new BN(Math.floor(Date.now() / 1000)),
+        false,
+        SELLER_FEE_BASIS_POINTS,
+        []
+      )
+      .accounts({
+        authority: wallet.publicKey,
+        memoryData: memoryDataPDA,
+        memory: itemMemory,
+        fingerprintRecord: await findFingerprintRecord(fingerprint),
+        mint: itemMint.publicKey,
+        tokenAccount: itemTokenAccount,
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([itemMint])
+      .rpc();
+    
+    const collectionItemAccounts = {
+      authority: wallet.publicKey,
+      memoryData: memoryDataPDA,
+      memory: itemMemory,
+      mint: itemMint.publicKey,
+      tokenAccount: itemTokenAccount,
+      metadata,
+      memoryCollection,
+      collectionMint: collectionMint.publicKey,
+      collectionMetadata,
+      collectionMasterEdition,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    };
+    
+    await program.methods
+      .addToCollection()
+      .accounts(collectionItemAccounts)
+      .rpc();
+    
+    let memory = await program.account.memory.fetch(itemMemory);
+    expect(memory.collection.toString()).to.equal(collectionMint.publicKey.toString());
+    collection = await program.account.memoryCollection.fetch(memoryCollection);
+    expect(collection.itemCount.toNumber()).to.equal(1);
+    
+    // A memory can only be in one collection at a time
+    try {
+      await program.methods
+        .addToCollection()
+        .accounts(collectionItemAccounts)
+        .rpc();
+      
+      expect.fail("Adding a memory twice should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyInCollection");
+    }
+    
+    await program.methods
+      .removeFromCollection()
+      .accounts(collectionItemAccounts)
+      .rpc();
+    
+    memory = await program.account.memory.fetch(itemMemory);
+    expect(memory.collection).to.be.null;
+    collection = await program.account.memoryCollection.fetch(memoryCollection);
+    expect(collection.itemCount.toNumber()).to.equal(0);
+    
+    // Once the token moves with a plain SPL transfer, the cached owner is
+    // stale but the former holder can no longer collect the memory
+    const newOwnerItemAccount = await getAssociatedTokenAddress(
+      itemMint.publicKey,
+      newOwnerKeypair.publicKey
+    );
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction()
+        .add(
+          createAssociatedTokenAccountInstruction(
+            wallet.publicKey,
+            newOwnerItemAccount,
+            newOwnerKeypair.publicKey,
+            itemMint.publicKey
+          )
+        )
+        .add(createTransferInstruction(itemTokenAccount, newOwnerItemAccount, wallet.publicKey, 1)),
+      [wallet.payer]
+    );
+    memory = await program.account.memory.fetch(itemMemory);
+    expect(memory.owner.toString()).to.equal(wallet.publicKey.toString());
+    
+    try {
+      await program.methods
+        .addToCollection()
+        .accounts({ ...collectionItemAccounts, tokenAccount: newOwnerItemAccount })
+        .rpc();
+      
+      expect.fail("Collecting a memory held by someone else should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("NotMemoryOwner");
+    }
+  });
+
+  it("Updates memory metadata and records the URI history", async () => {
     // Find the PDA for the first memory we created
     const [memoryPda, _] = await PublicKey.findProgramAddress(
       [Buffer.from("memory"), mintKeypair.publicKey.toBuffer()],
       program.programId
     );
+    const [memoryHistory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory-history"), memoryPda.toBuffer()],
+      program.programId
+    );
+    const { metadata } = await findMetadataAddresses(mintKeypair.publicKey);
+    const walletTokenAccount = await getAssociatedTokenAddress(
+      mintKeypair.publicKey,
+      wallet.publicKey
+    );
+    const newOwnerTokenAccount = await getAssociatedTokenAddress(
+      mintKeypair.publicKey,
+      newOwnerKeypair.publicKey
+    );
     
-    // New URI for the memory
-    const newUri = "https://ipfs.io/ipfs/newContentHash";
+    const updateAccounts = {
+      authority: wallet.publicKey,
+      memoryData: memoryDataPDA,
+      memory: memoryPda,
+      tokenAccount: walletTokenAccount,
+      memoryHistory,
+      metadata,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
     
-    // Update the memory URI
+    // Re-pin the content twice, renaming it on the second update
+    const firstHash = fingerprintOf("re-pinned-content-v1");
+    const secondHash = fingerprintOf("re-pinned-content-v2");
     await program.methods
-      .updateMemoryUri(newUri)
-      .accounts({
-        memory: memoryPda,
-        owner: wallet.publicKey,
-      })
+      .updateMemoryMetadata("https://ipfs.io/ipfs/contentHashV1", null, firstHash)
+      .accounts(updateAccounts)
+      .rpc();
+    await program.methods
+      .updateMemoryMetadata(
+        "https://ipfs.io/ipfs/contentHashV2",
+        "Renamed Memory",
+        secondHash
+      )
+      .accounts(updateAccounts)
       .rpc();
     
-    // Verify the memory URI was updated
+    // The metadata account now carries the new name and URI
+    const metadataInfo = await provider.connection.getAccountInfo(metadata);
+    const metadataText = metadataInfo.data.toString("utf8");
+    expect(metadataText).to.include("Renamed Memory");
+    expect(metadataText).to.include("https://ipfs.io/ipfs/contentHashV2");
+    
+    // Every replaced URI is kept with the hash it served
+    const history = await program.account.memoryHistory.fetch(memoryHistory);
+    expect(history.version).to.equal(2);
+    expect(history.contentHash).to.deep.equal(secondHash);
+    expect(history.entries.length).to.equal(2);
+    expect(history.entries[0].uri).to.equal("https://arweave.net/memory-data-uri");
+    expect(history.entries[1].uri).to.equal("https://ipfs.io/ipfs/contentHashV1");
+    expect(history.entries[1].contentHash).to.deep.equal(firstHash);
+    
+    // A previous holder cannot touch the metadata once the token has left them
+    try {
+      await program.methods
+        .updateMemoryMetadata("https://ipfs.io/ipfs/hijacked", null, firstHash)
+        .accounts({
+          ...updateAccounts,
+          authority: newOwnerKeypair.publicKey,
+          tokenAccount: newOwnerTokenAccount,
+        })
+        .signers([newOwnerKeypair])
+        .rpc();
+      
+      expect.fail("Updating another owner's memory should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("NotAuthorized");
+    }
+  });
+
+  it("Only lets the validator program set memory quality", async () => {
+    const [memoryPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory"), mintKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    // The owner cannot grade their own memory
+    try {
+      await program.methods
+        .setMemoryQuality(4)
+        .accounts({
+          validatorAuthority: wallet.publicKey,
+          memory: memoryPda,
+        })
+        .rpc();
+      
+      expect.fail("Setting quality without the validator program should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+    
     const memory = await program.account.memory.fetch(memoryPda);
-    expect(memory.uri).to.equal(newUri);
+    expect(memory.quality).to.equal(3);
   });
 
   it("Burns a memory NFT", async () => {
@@ -352,24 +909,33 @@ describe("memory_nft", () => {
       wallet.publicKey
     );
     
+    const { metadata, masterEdition } = await findMetadataAddresses(burnMintKeypair.publicKey);
+    const burnFingerprintRecord = await findFingerprintRecord(fingerprintOf("0xdeadbeef"));
+    
     // Mint a new memory for burning
     await program.methods
       .mintMemory(
+        "https://arweave.net/memory-data-uri",
         "Burn Test Memory",
         "BURN",
-        "https://arweave.net/memory-data-uri",
-        "therapeutic",
-        "legendary",
-        "0xdeadbeef",
-        new BN(Math.floor(Date.now() / 1000))
+        { therapeutic: {} },
+        4,
+        fingerprintOf("0xdeadbeef"),
+        new BN(Math.floor(Date.now() / 1000)),
+        false,
+        SELLER_FEE_BASIS_POINTS,
+        []
       )
       .accounts({
-        memory: memoryPda,
+        authority: wallet.publicKey,
         memoryData: memoryDataPDA,
+        memory: memoryPda,
+        fingerprintRecord: burnFingerprintRecord,
         mint: burnMintKeypair.publicKey,
         tokenAccount: tokenAccount,
-        authority: wallet.publicKey,
-        payer: wallet.publicKey,
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -378,23 +944,55 @@ describe("memory_nft", () => {
       .signers([burnMintKeypair])
       .rpc();
     
+    // Update it once so it has a metadata history to clean up
+    const [memoryHistory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory-history"), memoryPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .updateMemoryMetadata("https://arweave.net/burn-memory-v2", null, fingerprintOf("burn-v2"))
+      .accounts({
+        authority: wallet.publicKey,
+        memoryData: memoryDataPDA,
+        memory: memoryPda,
+        tokenAccount: tokenAccount,
+        memoryHistory,
+        metadata,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    
     // Get the initial count of memories
     const memoryDataBefore = await program.account.memoryData.fetch(memoryDataPDA);
     const initialCount = memoryDataBefore.memoryCount.toNumber();
     
+    // Listen for the burn event
+    let burnedEvent = null;
+    const listener = program.addEventListener("MemoryBurned", (event) => {
+      burnedEvent = event;
+    });
+    
     // Burn the memory NFT
     await program.methods
       .burnMemory()
       .accounts({
+        owner: wallet.publicKey,
+        memoryData: memoryDataPDA,
         memory: memoryPda,
+        fingerprintRecord: burnFingerprintRecord,
+        memoryHistory,
         mint: burnMintKeypair.publicKey,
         tokenAccount: tokenAccount,
-        owner: wallet.publicKey,
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
-        memoryData: memoryDataPDA,
       })
       .rpc();
     
+    await program.removeEventListener(listener);
+    
     // Try to fetch the memory - should fail as it's closed
     try {
       await program.account.memory.fetch(memoryPda);
@@ -404,8 +1002,24 @@ describe("memory_nft", () => {
       expect(error.toString()).to.include("Account does not exist");
     }
     
+    // The token account was closed as well
+    const tokenAccountInfo = await provider.connection.getAccountInfo(tokenAccount);
+    expect(tokenAccountInfo).to.be.null;
+    
+    // The fingerprint is released from the registry
+    const fingerprintInfo = await provider.connection.getAccountInfo(burnFingerprintRecord);
+    expect(fingerprintInfo).to.be.null;
+    
+    // So is the metadata history
+    const historyInfo = await provider.connection.getAccountInfo(memoryHistory);
+    expect(historyInfo).to.be.null;
+    
     // Verify the memory count was decremented
     const memoryDataAfter = await program.account.memoryData.fetch(memoryDataPDA);
     expect(memoryDataAfter.memoryCount.toNumber()).to.equal(initialCount - 1);
+    
+    // Verify the burn was announced to indexers
+    expect(burnedEvent).to.not.be.null;
+    expect(burnedEvent.mint.toString()).to.equal(burnMintKeypair.publicKey.toString());
   });
 }); 
\ No newline at end of file
@@ -1,7 +1,10 @@
 import * as anchor from '@project-serum/anchor';
 import { Program, BN } from '@project-serum/anchor';
 import { MemoryValidator } from '../target/types/memory_validator';
+import { MemoryNft } from '../target/types/memory_nft';
+import { NramToken } from '../target/types/nram_token';
 import { expect } from 'chai';
+import { createHash } from 'crypto';
 import {
   PublicKey,
   Keypair,
@@ -12,80 +15,354 @@ import {
 } from '@solana/web3.js';
 import {
   TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
   getAssociatedTokenAddress,
   createMint,
   mintTo,
   createAssociatedTokenAccount,
+  getAccount,
+  createTransferInstruction,
 } from '@solana/spl-token';
 
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
 describe('memory_validator', () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.MemoryValidator as Program<MemoryValidator>;
+  const memoryNft = anchor.workspace.MemoryNft as Program<MemoryNft>;
+  const nramToken = anchor.workspace.NramToken as Program<NramToken>;
   const wallet = provider.wallet;
   
   // Constants for testing
-  const MIN_STAKE_AMOUNT = new BN(100_000_000); // 0.1 SOL
-  const REWARD_PER_VALIDATION = new BN(5_000_000); // 0.005 SOL
-  const VALIDATION_THRESHOLD = 3; // Number of validations needed
+  const MIN_STAKE_AMOUNT = new BN(1000); // Program default
+  const REWARD_PER_VALIDATION = new BN(10); // Program default
+  const VALIDATION_THRESHOLD = 3; // Program default
+  const UNBONDING_SLOTS = 10; // Short cooldown so the tests can wait it out
+  const REPUTATION_NEUTRAL = 5000; // Reputation of a new validator
   
   // Test data
   let validatorConfigPda: PublicKey;
   let validatorConfigBump: number;
-  let validatorPda: PublicKey;
-  let validatorBump: number;
-  let validationPda: PublicKey;
-  let validationBump: number;
-  let mockMemoryMint: PublicKey;
-  let mockMemoryId: string;
+  let treasuryPda: PublicKey;
+  let stakingMint: PublicKey;
+  let rewardMint: PublicKey;
+  let tokenConfigPda: PublicKey;
+  let memoryDataPda: PublicKey;
+  let memoryId: PublicKey;
+  let validationRoundPda: PublicKey;
   
   // Test validators
-  const validator1 = wallet; // Use provider wallet as first validator
+  const validator1 = (wallet as anchor.Wallet).payer; // Use provider wallet as first validator
   const validator2 = Keypair.generate();
   const validator3 = Keypair.generate();
   const validator4 = Keypair.generate();
+  const validators = [validator1, validator2, validator3, validator4];
+  
+  // Owns the memories the validators vote on
+  const memoryOwner = Keypair.generate();
+  
+  const findValidator = async (authority: PublicKey) => {
+    const [validator] = await PublicKey.findProgramAddress(
+      [Buffer.from("validator"), authority.toBuffer()],
+      program.programId
+    );
+    return validator;
+  };
+  
+  // One validation PDA per memory and validator
+  const findValidation = async (memoryId: PublicKey, validator: PublicKey) => {
+    const [validation] = await PublicKey.findProgramAddress(
+      [Buffer.from("validation"), memoryId.toBuffer(), validator.toBuffer()],
+      program.programId
+    );
+    return validation;
+  };
+  
+  const findStakeVault = async (validator: PublicKey) => {
+    const [vault] = await PublicKey.findProgramAddress(
+      [Buffer.from("stake-vault"), validator.toBuffer()],
+      program.programId
+    );
+    return vault;
+  };
+  
+  const findValidationRound = async (memoryId: PublicKey) => {
+    const [round] = await PublicKey.findProgramAddress(
+      [Buffer.from("validation-round"), memoryId.toBuffer()],
+      program.programId
+    );
+    return round;
+  };
+  
+  // Mint a real memory NFT through the memory_nft program
+  const mintMemory = async (owner: Keypair, neuralData: string) => {
+    const mint = Keypair.generate();
+    const [memory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory"), mint.publicKey.toBuffer()],
+      memoryNft.programId
+    );
+    const fingerprint = [...createHash("sha256").update(neuralData).digest()];
+    const [fingerprintRecord] = await PublicKey.findProgramAddress(
+      [Buffer.from("fingerprint"), Buffer.from(fingerprint)],
+      memoryNft.programId
+    );
+    const [metadata] = await PublicKey.findProgramAddress(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const [masterEdition] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.publicKey.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    
+    await memoryNft.methods
+      .mintMemory(
+        "https://arweave.net/validated-memory",
+        "Validated Memory",
+        "VMEM",
+        { emotional: {} },
+        1,
+        fingerprint,
+        new BN(Math.floor(Date.now() / 1000)),
+        false,
+        0,
+        []
+      )
+      .accounts({
+        authority: owner.publicKey,
+        memoryData: memoryDataPda,
+        memory,
+        fingerprintRecord,
+        mint: mint.publicKey,
+        tokenAccount: await getAssociatedTokenAddress(mint.publicKey, owner.publicKey),
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner, mint])
+      .rpc();
+    
+    return memory;
+  };
+  
+  // The token account holding a memory NFT, which is its only account with a balance
+  const findHolderTokenAccount = async (memoryId: PublicKey) => {
+    const memory = await memoryNft.account.memory.fetchNullable(memoryId);
+    if (!memory) {
+      // Non-memories are rejected before the token account is looked at
+      return memoryId;
+    }
+    const { value } = await provider.connection.getTokenLargestAccounts(memory.mint);
+    return value[0].address;
+  };
+  
+  // Votes are sealed as sha256(validator, memory, is_valid, quality_score, salt)
+  // until revealed
+  const salts = new Map<string, Buffer>();
+  
+  const commitValidation = async (
+    signer: Keypair,
+    memoryId: PublicKey,
+    isValid: boolean,
+    qualityScore: number
+  ) => {
+    const validatorPda = await findValidator(signer.publicKey);
+    const validation = await findValidation(memoryId, validatorPda);
+    const salt = Keypair.generate().publicKey.toBuffer();
+    const commitment = [
+      ...createHash("sha256")
+        .update(validatorPda.toBuffer())
+        .update(memoryId.toBuffer())
+        .update(Buffer.from([isValid ? 1 : 0, qualityScore]))
+        .update(salt)
+        .digest(),
+    ];
+    
+    await program.methods
+      .commitValidation(commitment)
+      .accounts({
+        authority: signer.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        memory: memoryId,
+        memoryTokenAccount: await findHolderTokenAccount(memoryId),
+        validation,
+        validationRound: await findValidationRound(memoryId),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+    
+    salts.set(validation.toString(), salt);
+    return validation;
+  };
+  
+  // Reveals open once the cluster clock passes the round's commit deadline
+  const waitForRevealPhase = async (memoryId: PublicKey) => {
+    const round = await program.account.validationRound.fetch(await findValidationRound(memoryId));
+    const connection = provider.connection;
+    while ((await connection.getBlockTime(await connection.getSlot())) <= round.commitDeadline.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+  };
+  
+  const revealValidation = async (
+    signer: Keypair,
+    memoryId: PublicKey,
+    isValid: boolean,
+    qualityScore: number
+  ) => {
+    const validatorPda = await findValidator(signer.publicKey);
+    const validation = await findValidation(memoryId, validatorPda);
+    
+    await program.methods
+      .revealValidation(isValid, qualityScore, [...salts.get(validation.toString())])
+      .accounts({
+        authority: signer.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        validation,
+        validationRound: await findValidationRound(memoryId),
+      })
+      .signers([signer])
+      .rpc();
+  };
   
   before(async () => {
     // Find the program derived address for validator config
     [validatorConfigPda, validatorConfigBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator_config")],
+      [Buffer.from("validator-config")],
+      program.programId
+    );
+    [treasuryPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("treasury")],
       program.programId
     );
     
-    // Create a mock memory mint for testing
-    mockMemoryMint = await createMint(
+    // Staking and reward mints for the program
+    stakingMint = await createMint(provider.connection, validator1, wallet.publicKey, null, 0);
+    
+    // NRAM rewards are minted by nram_token, so its config PDA owns the mint
+    const rewardMintKeypair = Keypair.generate();
+    [tokenConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("token-config"), rewardMintKeypair.publicKey.toBuffer()],
+      nramToken.programId
+    );
+    rewardMint = await createMint(
       provider.connection,
-      wallet.payer,
-      wallet.publicKey,
+      validator1,
+      tokenConfigPda,
       null,
-      0
+      0,
+      rewardMintKeypair
     );
+    await nramToken.methods
+      .initialize(new BN(1_000_000))
+      .accounts({
+        authority: wallet.publicKey,
+        tokenConfig: tokenConfigPda,
+        mint: rewardMint,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await nramToken.methods
+      .setRewardMinter(validatorConfigPda)
+      .accounts({
+        authority: wallet.publicKey,
+        tokenConfig: tokenConfigPda,
+        mint: rewardMint,
+      })
+      .rpc();
     
-    mockMemoryId = mockMemoryMint.toString();
-    
-    // Fund the test validators
-    for (const validator of [validator2, validator3, validator4]) {
+    // Fund the test validators and the memory owner
+    for (const validator of [validator2, validator3, validator4, memoryOwner]) {
       const airdropSig = await provider.connection.requestAirdrop(
         validator.publicKey,
         2 * LAMPORTS_PER_SOL // 2 SOL
       );
       await provider.connection.confirmTransaction(airdropSig);
     }
+    
+    // The memory_nft program may already be initialized by its own tests
+    [memoryDataPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory-data")],
+      memoryNft.programId
+    );
+    if (!(await provider.connection.getAccountInfo(memoryDataPda))) {
+      await memoryNft.methods
+        .initialize()
+        .accounts({
+          authority: wallet.publicKey,
+          memoryData: memoryDataPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+    
+    memoryId = await mintMemory(memoryOwner, "validator-test-memory");
+    validationRoundPda = await findValidationRound(memoryId);
+  });
+
+  it("Lists every event in the IDL", async () => {
+    const events = program.idl.events.map((event) => event.name);
+    expect(events).to.include.members([
+      "ValidatorConfigInitialized",
+      "ValidatorConfigUpdated",
+      "ValidatorRegistered",
+      "ValidatorUnstaked",
+      "StakeWithdrawn",
+      "ValidatorReactivated",
+      "ValidationCommitted",
+      "ValidationRevealed",
+      "UnrevealedValidationPenalized",
+      "ValidationRoundFinalized",
+      "SlashingConfigUpdated",
+      "ConsensusConfigUpdated",
+      "ReputationConfigUpdated",
+      "CommissionUpdated",
+      "StakeDelegated",
+      "StakeUndelegated",
+      "DelegationWithdrawn",
+      "DelegationRewardsClaimed",
+      "ValidatorJailed",
+      "ValidatorUnjailed",
+      "JailConfigUpdated",
+      "TreasuryWithdrawn",
+      "ValidatorSlashed",
+      "SlashReversed",
+      "SlashFinalized",
+      "ValidationProcessed",
+      "MemoryQualityFinalized",
+      "RewardsClaimed",
+    ]);
   });
 
   it("Initializes the validator program", async () => {
-    const tx = await program.methods
-      .initialize(
-        MIN_STAKE_AMOUNT,
-        REWARD_PER_VALIDATION,
-        VALIDATION_THRESHOLD
-      )
+    await program.methods
+      .initialize()
       .accounts({
-        validatorConfig: validatorConfigPda,
         authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+        stakingTokenMint: stakingMint,
+        rewardTokenMint: rewardMint,
+        treasury: treasuryPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
       .rpc();
 
@@ -102,391 +379,649 @@ describe('memory_validator', () => {
   });
 
   it("Registers validators successfully", async () => {
-    // First, register the main wallet as validator1
-    [validatorPda, validatorBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator1.publicKey.toBuffer()],
-      program.programId
-    );
+    for (const signer of validators) {
+      // Every validator needs staking tokens
+      const stakingTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        validator1,
+        stakingMint,
+        signer.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        validator1,
+        stakingMint,
+        stakingTokenAccount,
+        validator1,
+        MIN_STAKE_AMOUNT.toNumber()
+      );
+      
+      const validatorPda = await findValidator(signer.publicKey);
+      const stakeVault = await findStakeVault(validatorPda);
+      await program.methods
+        .registerValidator(MIN_STAKE_AMOUNT)
+        .accounts({
+          authority: signer.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorPda,
+          stakingTokenAccount,
+          stakingTokenMint: stakingMint,
+          stakeVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([signer])
+        .rpc();
+      
+      // Verify validator was registered
+      const validatorAccount = await program.account.validator.fetch(validatorPda);
+      expect(validatorAccount.authority.toString()).to.equal(signer.publicKey.toString());
+      expect(validatorAccount.isActive).to.be.true;
+      expect(validatorAccount.stakeAmount.toString()).to.equal(MIN_STAKE_AMOUNT.toString());
+      expect(validatorAccount.validationsPerformed.toNumber()).to.equal(0);
+      expect(validatorAccount.rewardsEarned.toNumber()).to.equal(0);
+      
+      // The stake now sits in the vault
+      const vault = await getAccount(provider.connection, stakeVault);
+      expect(vault.amount.toString()).to.equal(validatorAccount.stakeAmount.toString());
+      const tokens = await getAccount(provider.connection, stakingTokenAccount);
+      expect(Number(tokens.amount)).to.equal(0);
+    }
     
+    // Verify we now have 4 validators
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.validatorCount.toNumber()).to.equal(4);
+  });
+
+  it("Finalizes a validation round once every commitment is revealed", async () => {
+    // A short commit phase the test can wait out
     await program.methods
-      .registerValidator()
+      .updateValidatorConfig(null, null, null, null, new BN(5), null, null)
       .accounts({
-        validator: validatorPda,
+        authority: wallet.publicKey,
         validatorConfig: validatorConfigPda,
-        authority: validator1.publicKey,
-        systemProgram: SystemProgram.programId,
       })
       .rpc();
     
-    // Verify validator was registered
-    let validatorAccount = await program.account.validator.fetch(validatorPda);
-    expect(validatorAccount.authority.toString()).to.equal(validator1.publicKey.toString());
-    expect(validatorAccount.active).to.be.true;
-    expect(validatorAccount.stake.toString()).to.equal(MIN_STAKE_AMOUNT.toString());
-    expect(validatorAccount.validationsSubmitted.toNumber()).to.equal(0);
-    expect(validatorAccount.validationsProcessed.toNumber()).to.equal(0);
-    expect(validatorAccount.pendingRewards.toString()).to.equal("0");
-    
-    // Verify validator count increased
-    let validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
-    expect(validatorConfig.validatorCount.toNumber()).to.equal(1);
+    const validation = await commitValidation(validator1, memoryId, true, 7);
     
-    // Now register validator2
-    const [validator2Pda, validator2Bump] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator2.publicKey.toBuffer()],
-      program.programId
-    );
+    // Only the commitment is public so far
+    const validationAccount = await program.account.validation.fetch(validation);
+    expect(validationAccount.memoryId.toString()).to.equal(memoryId.toString());
+    expect(validationAccount.isRevealed).to.be.false;
+    expect(validationAccount.isProcessed).to.be.false;
     
-    await program.methods
-      .registerValidator()
-      .accounts({
-        validator: validator2Pda,
-        validatorConfig: validatorConfigPda,
-        authority: validator2.publicKey,
-        systemProgram: SystemProgram.programId,
-      })
-      .signers([validator2])
-      .rpc();
+    // Reveals wait for the commit phase to close
+    try {
+      await revealValidation(validator1, memoryId, true, 7);
+      expect.fail("Revealing during the commit phase should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("RevealNotOpen");
+    }
     
-    // Verify validator2 was registered
-    validatorAccount = await program.account.validator.fetch(validator2Pda);
-    expect(validatorAccount.authority.toString()).to.equal(validator2.publicKey.toString());
-    expect(validatorAccount.active).to.be.true;
+    // Reaching the threshold does not close the commit phase early
+    await commitValidation(validator2, memoryId, true, 10);
+    await commitValidation(validator3, memoryId, false, 0);
+    let round = await program.account.validationRound.fetch(validationRoundPda);
+    expect(round.commits).to.equal(3);
+    expect(round.status).to.deep.equal({ commit: {} });
     
-    // Verify validator count increased again
-    validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
-    expect(validatorConfig.validatorCount.toNumber()).to.equal(2);
+    // Late commitments are refused once the deadline passes
+    await waitForRevealPhase(memoryId);
+    try {
+      await commitValidation(validator4, memoryId, true, 3);
+      expect.fail("Committing after the commit phase should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("CommitPeriodOver");
+    }
     
-    // Register validator3
-    const [validator3Pda, validator3Bump] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator3.publicKey.toBuffer()],
-      program.programId
-    );
+    // A reveal has to match its commitment
+    try {
+      await revealValidation(validator1, memoryId, true, 9);
+      expect.fail("Revealing a different vote should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidReveal");
+    }
     
+    await revealValidation(validator1, memoryId, true, 7);
+    await revealValidation(validator2, memoryId, true, 10);
+    round = await program.account.validationRound.fetch(validationRoundPda);
+    expect(round.validVotes).to.equal(2);
+    expect(round.status).to.deep.equal({ reveal: {} });
+    
+    // The last reveal finalizes the round
+    await revealValidation(validator3, memoryId, false, 0);
+    round = await program.account.validationRound.fetch(validationRoundPda);
+    expect(round.validVotes).to.equal(2);
+    expect(round.invalidVotes).to.equal(1);
+    expect(round.status).to.deep.equal({ accepted: {} });
+    expect(round.qualityScore).to.equal(7); // Lower median of 7 and 10
+  });
+
+  it("Writes the accepted quality back to the memory", async () => {
     await program.methods
-      .registerValidator()
+      .finalizeMemoryQuality()
       .accounts({
-        validator: validator3Pda,
         validatorConfig: validatorConfigPda,
-        authority: validator3.publicKey,
-        systemProgram: SystemProgram.programId,
+        validationRound: validationRoundPda,
+        memory: memoryId,
+        memoryNftProgram: memoryNft.programId,
       })
-      .signers([validator3])
       .rpc();
     
-    // Register validator4
-    const [validator4Pda, validator4Bump] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator4.publicKey.toBuffer()],
-      program.programId
+    // A median score of 7 maps onto quality tier 3
+    const memory = await memoryNft.account.memory.fetch(memoryId);
+    expect(memory.quality).to.equal(3);
+  });
+
+  it("Rejects a second vote on the same memory", async () => {
+    const otherMemory = await mintMemory(memoryOwner, "duplicate-vote-memory");
+    await commitValidation(validator4, otherMemory, true, 6);
+    
+    try {
+      await commitValidation(validator4, otherMemory, true, 9);
+      expect.fail("A second vote on the same memory should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("DuplicateValidation");
+    }
+    
+    // Only the first vote was counted
+    const round = await program.account.validationRound.fetch(
+      await findValidationRound(otherMemory)
     );
+    expect(round.commits).to.equal(1);
+  });
+
+  it("Rejects a reveal of a commitment copied from another validator", async () => {
+    const copiedMemory = await mintMemory(memoryOwner, "copied-commitment-memory");
+    const original = await commitValidation(validator1, copiedMemory, true, 8);
+    const { commitment } = await program.account.validation.fetch(original);
     
+    // Validator 3 commits validator 1's sealed vote as its own
+    const copierPda = await findValidator(validator3.publicKey);
+    const copy = await findValidation(copiedMemory, copierPda);
     await program.methods
-      .registerValidator()
+      .commitValidation(commitment)
       .accounts({
-        validator: validator4Pda,
+        authority: validator3.publicKey,
         validatorConfig: validatorConfigPda,
-        authority: validator4.publicKey,
+        validator: copierPda,
+        memory: copiedMemory,
+        memoryTokenAccount: await findHolderTokenAccount(copiedMemory),
+        validation: copy,
+        validationRound: await findValidationRound(copiedMemory),
         systemProgram: SystemProgram.programId,
       })
-      .signers([validator4])
+      .signers([validator3])
       .rpc();
     
-    // Verify we now have 4 validators
-    validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
-    expect(validatorConfig.validatorCount.toNumber()).to.equal(4);
+    // Once validator 1 reveals, its vote and salt are public, but they
+    // don't open a commitment bound to another validator
+    await waitForRevealPhase(copiedMemory);
+    await revealValidation(validator1, copiedMemory, true, 8);
+    salts.set(copy.toString(), salts.get(original.toString()));
+    try {
+      await revealValidation(validator3, copiedMemory, true, 8);
+      expect.fail("Revealing a copied commitment should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidReveal");
+    }
   });
 
-  it("Submits validations for a memory", async () => {
-    // Generate a unique validation ID
-    const validationId = Keypair.generate().publicKey.toString();
-    
-    // Find the validation PDA
-    [validationPda, validationBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("validation"), mockMemoryId, validationId],
-      program.programId
-    );
+  it("Rejects votes on accounts that are not memories", async () => {
+    try {
+      await commitValidation(validator4, Keypair.generate().publicKey, true, 5);
+      expect.fail("Voting on a random key should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("AccountNotInitialized");
+    }
+  });
+
+  it("Stops validators from validating their own memories", async () => {
+    const ownMemory = await mintMemory(validator4, "self-validation-memory");
     
-    // Find validator PDAs
-    const [validator1Pda, _] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator1.publicKey.toBuffer()],
-      program.programId
-    );
+    try {
+      await commitValidation(validator4, ownMemory, true, 10);
+      expect.fail("Validating an owned memory should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("CannotValidateOwnMemory");
+    }
     
-    const [validator2Pda, __] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator2.publicKey.toBuffer()],
-      program.programId
+    // A plain SPL transfer leaves the cached owner behind, but the holder
+    // still cannot vote on the memory
+    const boughtMemory = await mintMemory(memoryOwner, "self-validation-bought-memory");
+    const { mint } = await memoryNft.account.memory.fetch(boughtMemory);
+    const buyerTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      validator4,
+      mint,
+      validator4.publicKey
     );
-    
-    const [validator3Pda, ___] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator3.publicKey.toBuffer()],
-      program.programId
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        createTransferInstruction(
+          await getAssociatedTokenAddress(mint, memoryOwner.publicKey),
+          buyerTokenAccount,
+          memoryOwner.publicKey,
+          1
+        )
+      ),
+      [memoryOwner]
     );
     
-    // Submit validation from validator1
+    try {
+      await commitValidation(validator4, boughtMemory, true, 10);
+      expect.fail("Validating a held memory should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("CannotValidateOwnMemory");
+    }
+  });
+
+  it("Penalizes validators that commit but never reveal", async () => {
+    // One-second phases so the round runs out during the test
     await program.methods
-      .submitValidation(
-        mockMemoryId,
-        validationId,
-        "0x1234567890",  // Neural fingerprint
-        "excellent", // Quality score
-        "This is an authentic memory", // Description
-        new BN(Date.now())
-      )
+      .updateValidatorConfig(null, null, null, null, new BN(1), new BN(1), null)
       .accounts({
-        validation: validationPda,
-        validator: validator1Pda,
-        authority: validator1.publicKey,
+        authority: wallet.publicKey,
         validatorConfig: validatorConfigPda,
-        systemProgram: SystemProgram.programId,
       })
       .rpc();
     
-    // Verify validation was created
-    let validation = await program.account.validation.fetch(validationPda);
-    expect(validation.memoryId).to.equal(mockMemoryId);
-    expect(validation.validationId).to.equal(validationId);
-    expect(validation.neuralFingerprint).to.equal("0x1234567890");
-    expect(validation.quality).to.equal("excellent");
-    expect(validation.description).to.equal("This is an authentic memory");
-    expect(validation.processed).to.be.false;
-    expect(validation.validators.length).to.equal(1);
-    expect(validation.validators[0].toString()).to.equal(validator1.publicKey.toString());
+    const silentMemory = await mintMemory(memoryOwner, "unrevealed-vote-memory");
+    const silentRound = await findValidationRound(silentMemory);
+    const validation = await commitValidation(validator4, silentMemory, true, 8);
+    await new Promise((resolve) => setTimeout(resolve, 4000));
     
-    // Validator1's stats should be updated
-    let validator1Account = await program.account.validator.fetch(validator1Pda);
-    expect(validator1Account.validationsSubmitted.toNumber()).to.equal(1);
-    
-    // Now validator2 submits validation for the same memory
+    // Anyone can close the round once the reveal deadline has passed
     await program.methods
-      .submitValidation(
-        mockMemoryId,
-        validationId,
-        "0x1234567890",  // Same neural fingerprint
-        "excellent", // Same quality score
-        "Verified authentic memory", // Different description
-        new BN(Date.now())
-      )
+      .finalizeRound()
       .accounts({
-        validation: validationPda,
-        validator: validator2Pda,
-        authority: validator2.publicKey,
         validatorConfig: validatorConfigPda,
-        systemProgram: SystemProgram.programId,
+        validationRound: silentRound,
       })
-      .signers([validator2])
       .rpc();
+    let round = await program.account.validationRound.fetch(silentRound);
+    expect(round.status).to.deep.equal({ expired: {} });
     
-    // Verify validation was updated
-    validation = await program.account.validation.fetch(validationPda);
-    expect(validation.validators.length).to.equal(2);
-    expect(validation.validators[1].toString()).to.equal(validator2.publicKey.toString());
-    
-    // Validator2's stats should be updated
-    let validator2Account = await program.account.validator.fetch(validator2Pda);
-    expect(validator2Account.validationsSubmitted.toNumber()).to.equal(1);
+    // The expired round can't reopen while a vote in it is unsettled
+    try {
+      await commitValidation(validator1, silentMemory, true, 8);
+      expect.fail("Reopening an unsettled round should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("RoundNotSettled");
+    }
     
-    // Now validator3 submits validation, which should meet the threshold
+    const validatorPda = await findValidator(validator4.publicKey);
     await program.methods
-      .submitValidation(
-        mockMemoryId,
-        validationId,
-        "0x1234567890",  // Same neural fingerprint
-        "excellent", // Same quality score
-        "Confirmed authenticity", // Different description
-        new BN(Date.now())
-      )
+      .penalizeUnrevealed()
       .accounts({
-        validation: validationPda,
-        validator: validator3Pda,
-        authority: validator3.publicKey,
         validatorConfig: validatorConfigPda,
-        systemProgram: SystemProgram.programId,
+        validator: validatorPda,
+        validation,
+        validationRound: silentRound,
       })
-      .signers([validator3])
       .rpc();
     
-    // Verify validation was updated
-    validation = await program.account.validation.fetch(validationPda);
-    expect(validation.validators.length).to.equal(3);
-    expect(validation.validators[2].toString()).to.equal(validator3.publicKey.toString());
+    // A missed reveal makes the validator slashable at once
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    const validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.deviations).to.equal(validatorConfig.maxDeviations);
+    let validationAccount = await program.account.validation.fetch(validation);
+    expect(validationAccount.isProcessed).to.be.true;
     
-    // Validator3's stats should be updated
-    let validator3Account = await program.account.validator.fetch(validator3Pda);
-    expect(validator3Account.validationsSubmitted.toNumber()).to.equal(1);
+    // Once settled, the memory gets a fresh round and the same validator
+    // can vote on it again
+    await commitValidation(validator4, silentMemory, true, 8);
+    round = await program.account.validationRound.fetch(silentRound);
+    expect(round.round).to.equal(1);
+    expect(round.commits).to.equal(1);
+    expect(round.unsettled).to.equal(1);
+    expect(round.status).to.deep.equal({ commit: {} });
+    validationAccount = await program.account.validation.fetch(validation);
+    expect(validationAccount.round).to.equal(1);
+    expect(validationAccount.isProcessed).to.be.false;
   });
 
-  it("Processes validations and rewards validators", async () => {
-    // Find validator PDAs
-    const [validator1Pda, _] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator1.publicKey.toBuffer()],
-      program.programId
-    );
+  it("Lets anyone process a finalized validation for a tip", async () => {
+    const validatorPda = await findValidator(validator1.publicKey);
+    const validation = await findValidation(memoryId, validatorPda);
+    const tip = new BN(2);
     
-    const [validator2Pda, __] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator2.publicKey.toBuffer()],
-      program.programId
-    );
+    await program.methods
+      .updateValidatorConfig(null, null, null, null, null, null, tip)
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
     
-    const [validator3Pda, ___] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator3.publicKey.toBuffer()],
-      program.programId
+    // Validator 4 cranks validator 1's validation
+    const crankerTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      validator4,
+      rewardMint,
+      validator4.publicKey
     );
-    
-    // Process the validation
     await program.methods
-      .processValidation(mockMemoryId, validationPda)
+      .processValidation()
       .accounts({
-        validation: validationPda,
+        cranker: validator4.publicKey,
         validatorConfig: validatorConfigPda,
-        authority: wallet.publicKey,
+        validator: validatorPda,
+        validation,
+        validationRound: validationRoundPda,
+        crankerTokenAccount,
+        rewardTokenMint: rewardMint,
+        tokenConfig: tokenConfigPda,
+        nramTokenProgram: nramToken.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
+      .signers([validator4])
       .rpc();
     
-    // Verify validation is marked as processed
-    const validation = await program.account.validation.fetch(validationPda);
-    expect(validation.processed).to.be.true;
+    await program.methods
+      .updateValidatorConfig(null, null, null, null, null, null, new BN(0))
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
     
-    // Check that validators received their rewards
-    const validator1Account = await program.account.validator.fetch(validator1Pda);
-    expect(validator1Account.validationsProcessed.toNumber()).to.equal(1);
-    expect(validator1Account.pendingRewards.toString()).to.equal(REWARD_PER_VALIDATION.toString());
+    // Verify validation is marked as processed
+    const validationAccount = await program.account.validation.fetch(validation);
+    expect(validationAccount.isProcessed).to.be.true;
     
-    const validator2Account = await program.account.validator.fetch(validator2Pda);
-    expect(validator2Account.validationsProcessed.toNumber()).to.equal(1);
-    expect(validator2Account.pendingRewards.toString()).to.equal(REWARD_PER_VALIDATION.toString());
+    // Check that the validator earned its reward at neutral reputation, and
+    // gained reputation for agreeing with consensus
+    const validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.rewardsEarned.toString()).to.equal(REWARD_PER_VALIDATION.toString());
+    expect(validatorAccount.reputation).to.equal(REPUTATION_NEUTRAL + 100);
     
-    const validator3Account = await program.account.validator.fetch(validator3Pda);
-    expect(validator3Account.validationsProcessed.toNumber()).to.equal(1);
-    expect(validator3Account.pendingRewards.toString()).to.equal(REWARD_PER_VALIDATION.toString());
+    // The cranker got its tip in NRAM
+    const crankerAccount = await getAccount(provider.connection, crankerTokenAccount);
+    expect(Number(crankerAccount.amount)).to.equal(tip.toNumber());
   });
 
-  it("Claims validator rewards", async () => {
-    // Find validator1's PDA
-    const [validator1Pda, _] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator1.publicKey.toBuffer()],
-      program.programId
-    );
-    
-    // Get validator's initial balance
-    const initialBalance = await provider.connection.getBalance(validator1.publicKey);
-    
-    // Claim rewards
+  it("Slashes a validator that disagrees with consensus and reverses it on appeal", async () => {
+    // Slash on the first disagreement so the test doesn't need more rounds
     await program.methods
-      .claimRewards()
+      .updateSlashingConfig(null, 1, null, null, null)
       .accounts({
-        validator: validator1Pda,
+        authority: wallet.publicKey,
         validatorConfig: validatorConfigPda,
-        authority: validator1.publicKey,
-        systemProgram: SystemProgram.programId,
       })
       .rpc();
     
-    // Verify rewards were claimed
-    const validator1Account = await program.account.validator.fetch(validator1Pda);
-    expect(validator1Account.pendingRewards.toString()).to.equal("0");
-    
-    // Check that balance increased
-    const finalBalance = await provider.connection.getBalance(validator1.publicKey);
-    expect(finalBalance).to.be.gt(initialBalance);
-  });
-
-  it("Updates validator config parameters", async () => {
-    const newMinStake = new BN(200_000_000); // 0.2 SOL
-    const newReward = new BN(10_000_000); // 0.01 SOL
-    const newThreshold = 5;
-    
+    // Validator 3 voted the accepted memory invalid
+    const validatorPda = await findValidator(validator3.publicKey);
     await program.methods
-      .updateValidatorConfig(
-        newMinStake,
-        newReward,
-        newThreshold
-      )
+      .processValidation()
       .accounts({
+        cranker: wallet.publicKey,
         validatorConfig: validatorConfigPda,
-        authority: wallet.publicKey,
+        validator: validatorPda,
+        validation: await findValidation(memoryId, validatorPda),
+        validationRound: validationRoundPda,
+        crankerTokenAccount: await getAssociatedTokenAddress(rewardMint, validator4.publicKey),
+        rewardTokenMint: rewardMint,
+        tokenConfig: tokenConfigPda,
+        nramTokenProgram: nramToken.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
     
-    // Verify config was updated
-    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
-    expect(validatorConfig.minStakeAmount.toString()).to.equal(newMinStake.toString());
-    expect(validatorConfig.rewardPerValidation.toString()).to.equal(newReward.toString());
-    expect(validatorConfig.validationThreshold).to.equal(newThreshold);
-  });
-
-  it("Allows validators to unstake and become inactive", async () => {
-    // Find validator2's PDA
-    const [validator2Pda, _] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator2.publicKey.toBuffer()],
+    let validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.rewardsEarned.toNumber()).to.equal(0);
+    expect(validatorAccount.deviations).to.equal(1);
+    expect(validatorAccount.reputation).to.equal(REPUTATION_NEUTRAL - 200);
+    
+    const stakeVault = await findStakeVault(validatorPda);
+    const [slashRecord] = await PublicKey.findProgramAddress(
+      [Buffer.from("slash"), validatorPda.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
       program.programId
     );
     
-    // Get initial balance
-    const initialBalance = await provider.connection.getBalance(validator2.publicKey);
-    
-    // Unstake
+    // Anyone can apply the slash once the limit is reached
     await program.methods
-      .unstake()
+      .slashValidator()
       .accounts({
-        validator: validator2Pda,
+        payer: wallet.publicKey,
         validatorConfig: validatorConfigPda,
-        authority: validator2.publicKey,
+        validator: validatorPda,
+        stakeVault,
+        treasury: treasuryPda,
+        slashRecord,
+        tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
-      .signers([validator2])
       .rpc();
     
-    // Verify validator is now inactive
-    const validator2Account = await program.account.validator.fetch(validator2Pda);
-    expect(validator2Account.active).to.be.false;
-    expect(validator2Account.stake.toString()).to.equal("0");
-    
-    // Check that balance increased by stake amount
-    const finalBalance = await provider.connection.getBalance(validator2.publicKey);
-    expect(finalBalance).to.be.gt(initialBalance);
+    // 5% of the stake moved to the treasury
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.stakeAmount.toNumber()).to.equal(950);
+    expect(validatorAccount.deviations).to.equal(0);
+    let vault = await getAccount(provider.connection, stakeVault);
+    expect(Number(vault.amount)).to.equal(950);
+    let treasury = await getAccount(provider.connection, treasuryPda);
+    expect(Number(treasury.amount)).to.equal(50);
     
-    // Verify validator count decreased
-    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
-    expect(validatorConfig.validatorCount.toNumber()).to.equal(3); // Down from 4
-  });
-
-  it("Prevents inactive validators from submitting validations", async () => {
-    // Generate a unique validation ID
-    const newValidationId = Keypair.generate().publicKey.toString();
+    let record = await program.account.slashRecord.fetch(slashRecord);
+    expect(record.amount.toNumber()).to.equal(50);
+    expect(record.status).to.deep.equal({ pending: {} });
     
-    // Find the validation PDA
-    const [newValidationPda, _] = await PublicKey.findProgramAddress(
-      [Buffer.from("validation"), mockMemoryId, newValidationId],
-      program.programId
+    // Stake open to appeal cannot be withdrawn from the treasury
+    let validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.pendingSlashed.toNumber()).to.equal(50);
+    try {
+      await program.methods
+        .withdrawTreasury(new BN(50))
+        .accounts({
+          authority: wallet.publicKey,
+          validatorConfig: validatorConfigPda,
+          treasury: treasuryPda,
+          destination: await getAssociatedTokenAddress(stakingMint, validator1.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Withdrawing a pending slash should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientTreasuryBalance");
+    }
+    
+    // The authority reverses it inside the appeal window
+    await program.methods
+      .reverseSlash()
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        stakeVault,
+        treasury: treasuryPda,
+        slashRecord,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.stakeAmount.toNumber()).to.equal(1000);
+    vault = await getAccount(provider.connection, stakeVault);
+    expect(Number(vault.amount)).to.equal(1000);
+    treasury = await getAccount(provider.connection, treasuryPda);
+    expect(Number(treasury.amount)).to.equal(0);
+    record = await program.account.slashRecord.fetch(slashRecord);
+    expect(record.status).to.deep.equal({ reversed: {} });
+    validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.pendingSlashed.toNumber()).to.equal(0);
+  });
+
+  it("Claims validator rewards", async () => {
+    const validatorPda = await findValidator(validator1.publicKey);
+    const rewardTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      validator1,
+      rewardMint,
+      validator1.publicKey
     );
     
-    // Find validator2's PDA (the inactive validator)
-    const [validator2Pda, __] = await PublicKey.findProgramAddress(
-      [Buffer.from("validator"), validator2.publicKey.toBuffer()],
-      program.programId
+    const supplyBefore = (await nramToken.account.tokenConfig.fetch(tokenConfigPda)).circulatingSupply;
+    
+    // Claim rewards
+    await program.methods
+      .claimRewards()
+      .accounts({
+        authority: validator1.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        rewardTokenAccount,
+        rewardTokenMint: rewardMint,
+        tokenConfig: tokenConfigPda,
+        nramTokenProgram: nramToken.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    
+    // Verify rewards were claimed and actually paid out in NRAM
+    const validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.rewardsEarned.toString()).to.equal("0");
+    const rewardAccount = await getAccount(provider.connection, rewardTokenAccount);
+    expect(Number(rewardAccount.amount)).to.equal(REWARD_PER_VALIDATION.toNumber());
+    const tokenConfig = await nramToken.account.tokenConfig.fetch(tokenConfigPda);
+    expect(tokenConfig.circulatingSupply.sub(supplyBefore).toString())
+      .to.equal(REWARD_PER_VALIDATION.toString());
+  });
+
+  it("Updates validator config parameters", async () => {
+    const newMinStake = new BN(2000);
+    const newReward = new BN(20);
+    const newThreshold = 5;
+    const newUnbondingSlots = new BN(UNBONDING_SLOTS);
+    
+    await program.methods
+      .updateValidatorConfig(newMinStake, newReward, newThreshold, newUnbondingSlots, null, null, null)
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    
+    // Verify config was updated
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.minStakeAmount.toString()).to.equal(newMinStake.toString());
+    expect(validatorConfig.rewardPerValidation.toString()).to.equal(newReward.toString());
+    expect(validatorConfig.validationThreshold).to.equal(newThreshold);
+    expect(validatorConfig.unbondingSlots.toNumber()).to.equal(UNBONDING_SLOTS);
+  });
+
+  it("Unbonds before a validator can withdraw its stake", async () => {
+    const validatorPda = await findValidator(validator2.publicKey);
+    const stakeVault = await findStakeVault(validatorPda);
+    const stakingTokenAccount = await getAssociatedTokenAddress(
+      stakingMint,
+      validator2.publicKey
     );
+    const withdrawAccounts = {
+      authority: validator2.publicKey,
+      validatorConfig: validatorConfigPda,
+      validator: validatorPda,
+      stakingTokenAccount,
+      stakeVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
     
-    // Try to submit validation as inactive validator (should fail)
+    // Request to unstake
+    await program.methods
+      .requestUnstake()
+      .accounts({
+        authority: validator2.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+      })
+      .signers([validator2])
+      .rpc();
+    
+    // The validator is inactive but its stake stays escrowed
+    let validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.isActive).to.be.false;
+    expect(validatorAccount.stakeAmount.toString()).to.equal(MIN_STAKE_AMOUNT.toString());
+    expect(validatorAccount.unbondingUntil.toNumber()).to.be.greaterThan(0);
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.validatorCount.toNumber()).to.equal(3);
+    
+    // Withdrawing during the cooldown fails
     try {
       await program.methods
-        .submitValidation(
-          mockMemoryId,
-          newValidationId,
-          "0x9876543210",  // Neural fingerprint
-          "common", // Quality score
-          "This should fail", // Description
-          new BN(Date.now())
-        )
-        .accounts({
-          validation: newValidationPda,
-          validator: validator2Pda,
-          authority: validator2.publicKey,
-          validatorConfig: validatorConfigPda,
-          systemProgram: SystemProgram.programId,
-        })
+        .withdrawStake()
+        .accounts(withdrawAccounts)
+        .signers([validator2])
+        .rpc();
+      expect.fail("Withdrawing before the unbonding period ends should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("UnbondingNotComplete");
+    }
+    
+    // Wait out the cooldown
+    while ((await provider.connection.getSlot()) < validatorAccount.unbondingUntil.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    
+    // Its vote on the first memory is still unprocessed, so the stake stays
+    // where a slash can reach it
+    expect(validatorAccount.openValidations).to.equal(1);
+    try {
+      await program.methods
+        .withdrawStake()
+        .accounts(withdrawAccounts)
         .signers([validator2])
         .rpc();
+      expect.fail("Withdrawing with an open vote should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("OpenValidations");
+    }
+    
+    // Once the vote is processed the stake is free to go
+    await program.methods
+      .processValidation()
+      .accounts({
+        cranker: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        validation: await findValidation(memoryId, validatorPda),
+        validationRound: validationRoundPda,
+        crankerTokenAccount: await getAssociatedTokenAddress(rewardMint, validator4.publicKey),
+        rewardTokenMint: rewardMint,
+        tokenConfig: tokenConfigPda,
+        nramTokenProgram: nramToken.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .withdrawStake()
+      .accounts(withdrawAccounts)
+      .signers([validator2])
+      .rpc();
+    
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.openValidations).to.equal(0);
+    expect(validatorAccount.stakeAmount.toString()).to.equal("0");
+    expect(validatorAccount.unbondingUntil.toNumber()).to.equal(0);
+    
+    // The vault balance still matches the recorded stake, and the tokens are back
+    const vault = await getAccount(provider.connection, stakeVault);
+    expect(Number(vault.amount)).to.equal(0);
+    const tokens = await getAccount(provider.connection, stakingTokenAccount);
+    expect(tokens.amount.toString()).to.equal(MIN_STAKE_AMOUNT.toString());
+  });
+
+  it("Prevents inactive validators from submitting validations", async () => {
+    // Try to submit validation as inactive validator (should fail)
+    try {
+      await commitValidation(validator2, await mintMemory(memoryOwner, "inactive-vote-memory"), true, 5);
       
       // If we reach here, the validation did not fail as expected
       expect.fail("Validation from inactive validator should have failed");
@@ -495,4 +1030,598 @@ describe('memory_validator', () => {
       expect(error.toString()).to.include("Validator is not active");
     }
   });
-}); 
\ No newline at end of file
+
+  it("Reactivates a validator on its existing account", async () => {
+    const validatorPda = await findValidator(validator2.publicKey);
+    const stakeVault = await findStakeVault(validatorPda);
+    const stakingTokenAccount = await getAssociatedTokenAddress(
+      stakingMint,
+      validator2.publicKey
+    );
+    
+    // The minimum stake went up, so top up the wallet first
+    await mintTo(
+      provider.connection,
+      validator1,
+      stakingMint,
+      stakingTokenAccount,
+      validator1,
+      1000
+    );
+    
+    await program.methods
+      .reactivateValidator(new BN(2000))
+      .accounts({
+        authority: validator2.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        stakingTokenAccount,
+        stakeVault,
+        treasury: treasuryPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([validator2])
+      .rpc();
+    
+    const validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.isActive).to.be.true;
+    expect(validatorAccount.stakeAmount.toNumber()).to.equal(2000);
+    const vault = await getAccount(provider.connection, stakeVault);
+    expect(Number(vault.amount)).to.equal(2000);
+    
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.validatorCount.toNumber()).to.equal(4);
+  });
+
+  it("Weights round votes by capped stake when enabled", async () => {
+    // Two-vote rounds with a short commit phase and room to reveal
+    await program.methods
+      .updateValidatorConfig(null, null, 2, null, new BN(5), new BN(3600), null)
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    await program.methods
+      .updateConsensusConfig(true, new BN(1500))
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    
+    // Validator 2 staked 2000, capped at 1500; validator 1 staked 1000.
+    // Their earlier agreeing votes lifted both reputations 2% above neutral,
+    // while validator 3's disagreement left it 4% below
+    const weightedMemory = await mintMemory(memoryOwner, "stake-weighted-memory");
+    const weightedRound = await findValidationRound(weightedMemory);
+    await commitValidation(validator1, weightedMemory, true, 6);
+    await commitValidation(validator3, weightedMemory, false, 0);
+    
+    // The threshold is met, but validator 2 can still commit
+    await commitValidation(validator2, weightedMemory, false, 0);
+    
+    // Stake delegated to validator 1 after it committed doesn't add to its vote
+    const delegator = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(delegator.publicKey, LAMPORTS_PER_SOL)
+    );
+    const delegatorTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      validator1,
+      stakingMint,
+      delegator.publicKey
+    );
+    await mintTo(provider.connection, validator1, stakingMint, delegatorTokenAccount, validator1, 400);
+    const validator1Pda = await findValidator(validator1.publicKey);
+    const [delegation] = await PublicKey.findProgramAddress(
+      [Buffer.from("delegation"), validator1Pda.toBuffer(), delegator.publicKey.toBuffer()],
+      program.programId
+    );
+    const [delegationVault] = await PublicKey.findProgramAddress(
+      [Buffer.from("delegation-vault"), validator1Pda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .delegate(new BN(400))
+      .accounts({
+        delegator: delegator.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validator1Pda,
+        delegation,
+        delegatorTokenAccount,
+        stakingTokenMint: stakingMint,
+        delegationVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([delegator])
+      .rpc();
+    
+    await waitForRevealPhase(weightedMemory);
+    await revealValidation(validator1, weightedMemory, true, 6);
+    await revealValidation(validator3, weightedMemory, false, 0);
+    await revealValidation(validator2, weightedMemory, false, 0);
+    
+    // Every commitment counts, weighted by stake and reputation
+    const round = await program.account.validationRound.fetch(weightedRound);
+    expect(round.commits).to.equal(3);
+    expect(round.validVotes).to.equal(1);
+    expect(round.invalidVotes).to.equal(2);
+    expect(round.validWeight.toNumber()).to.equal(1020); // Not (1000 + 400) * 1.02
+    expect(round.invalidWeight.toNumber()).to.equal(960 + 1530);
+    expect(round.status).to.deep.equal({ rejected: {} });
+    
+    // Back to one validator, one vote
+    await program.methods
+      .updateConsensusConfig(false, null)
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+  });
+
+  it("Shares rewards with delegators and unbonds their stake", async () => {
+    const delegator = Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      delegator.publicKey,
+      2 * LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const delegatorTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      validator1,
+      stakingMint,
+      delegator.publicKey
+    );
+    await mintTo(provider.connection, validator1, stakingMint, delegatorTokenAccount, validator1, 2000);
+    
+    // Validator 2 keeps 10% of what its delegators earn
+    const validatorPda = await findValidator(validator2.publicKey);
+    await program.methods
+      .setCommission(1000)
+      .accounts({
+        authority: validator2.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+      })
+      .signers([validator2])
+      .rpc();
+    
+    const [delegation] = await PublicKey.findProgramAddress(
+      [Buffer.from("delegation"), validatorPda.toBuffer(), delegator.publicKey.toBuffer()],
+      program.programId
+    );
+    const [delegationVault] = await PublicKey.findProgramAddress(
+      [Buffer.from("delegation-vault"), validatorPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .delegate(new BN(2000))
+      .accounts({
+        delegator: delegator.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        delegation,
+        delegatorTokenAccount,
+        stakingTokenMint: stakingMint,
+        delegationVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([delegator])
+      .rpc();
+    
+    let validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.delegatedStake.toNumber()).to.equal(2000);
+    let vault = await getAccount(provider.connection, delegationVault);
+    expect(Number(vault.amount)).to.equal(2000);
+    
+    // Validator 2 earns a 20 token reward on half delegated stake
+    const rewardsBefore = validatorAccount.rewardsEarned.toNumber();
+    const delegatedMemory = await mintMemory(memoryOwner, "delegated-stake-memory");
+    await commitValidation(validator1, delegatedMemory, true, 6);
+    await commitValidation(validator2, delegatedMemory, true, 6);
+    await waitForRevealPhase(delegatedMemory);
+    await revealValidation(validator1, delegatedMemory, true, 6);
+    await revealValidation(validator2, delegatedMemory, true, 6);
+    await program.methods
+      .processValidation()
+      .accounts({
+        cranker: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        validation: await findValidation(delegatedMemory, validatorPda),
+        validationRound: await findValidationRound(delegatedMemory),
+        crankerTokenAccount: await getAssociatedTokenAddress(rewardMint, wallet.publicKey),
+        rewardTokenMint: rewardMint,
+        tokenConfig: tokenConfigPda,
+        nramTokenProgram: nramToken.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    
+    // 10 of it belongs to the delegated half, less a 1 token commission
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.rewardsEarned.toNumber() - rewardsBefore).to.equal(11);
+    
+    const rewardTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      delegator,
+      rewardMint,
+      delegator.publicKey
+    );
+    await program.methods
+      .claimDelegationRewards()
+      .accounts({
+        delegator: delegator.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        delegation,
+        rewardTokenAccount,
+        rewardTokenMint: rewardMint,
+        tokenConfig: tokenConfigPda,
+        nramTokenProgram: nramToken.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([delegator])
+      .rpc();
+    const rewards = await getAccount(provider.connection, rewardTokenAccount);
+    expect(Number(rewards.amount)).to.equal(9);
+    
+    // Undelegated tokens wait out the unbonding period
+    const undelegateAccounts = {
+      delegator: delegator.publicKey,
+      validatorConfig: validatorConfigPda,
+      validator: validatorPda,
+      delegation,
+    };
+    await program.methods
+      .undelegate(new BN(1500))
+      .accounts(undelegateAccounts)
+      .signers([delegator])
+      .rpc();
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.delegatedStake.toNumber()).to.equal(500);
+    
+    // A second request can't push back the first one's release
+    try {
+      await program.methods
+        .undelegate(new BN(500))
+        .accounts(undelegateAccounts)
+        .signers([delegator])
+        .rpc();
+      expect.fail("Undelegating while tokens are unbonding should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyUnbonding");
+    }
+    
+    const withdrawAccounts = {
+      delegator: delegator.publicKey,
+      validatorConfig: validatorConfigPda,
+      validator: validatorPda,
+      delegation,
+      delegatorTokenAccount,
+      delegationVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    try {
+      await program.methods
+        .withdrawDelegation()
+        .accounts(withdrawAccounts)
+        .signers([delegator])
+        .rpc();
+      expect.fail("Withdrawing before the unbonding period ends should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("UnbondingNotComplete");
+    }
+    
+    const delegationAccount = await program.account.delegation.fetch(delegation);
+    while ((await provider.connection.getSlot()) < delegationAccount.unbondingUntil.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await program.methods
+      .withdrawDelegation()
+      .accounts(withdrawAccounts)
+      .signers([delegator])
+      .rpc();
+    
+    vault = await getAccount(provider.connection, delegationVault);
+    expect(Number(vault.amount)).to.equal(500);
+    const tokens = await getAccount(provider.connection, delegatorTokenAccount);
+    expect(Number(tokens.amount)).to.equal(1500);
+  });
+
+  it("Jails an idle validator until it unjails for a fee", async () => {
+    const validatorPda = await findValidator(validator2.publicKey);
+    const jailAccounts = {
+      validatorConfig: validatorConfigPda,
+      validator: validatorPda,
+    };
+    
+    // Validator 2 voted moments ago, so it can't be jailed yet
+    await program.methods
+      .updateJailConfig(new BN(3600), new BN(2), new BN(100))
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    try {
+      await program.methods.jailValidator().accounts(jailAccounts).rpc();
+      expect.fail("Jailing an active validator should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("ValidatorNotIdle");
+    }
+    
+    // With a one-second window it soon counts as idle
+    await program.methods
+      .updateJailConfig(new BN(1), null, null)
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    
+    const countBefore = (await program.account.validatorConfig.fetch(validatorConfigPda)).validatorCount;
+    await program.methods.jailValidator().accounts(jailAccounts).rpc();
+    
+    let validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.isActive).to.be.false;
+    expect(validatorAccount.jailedUntil.toNumber()).to.be.greaterThan(0);
+    let validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.validatorCount.toNumber()).to.equal(countBefore.toNumber() - 1);
+    
+    // Jailed validators are out of the rounds
+    const jailedMemory = await mintMemory(memoryOwner, "jailed-validator-memory");
+    try {
+      await commitValidation(validator2, jailedMemory, true, 5);
+      expect.fail("A jailed validator should not be able to commit");
+    } catch (error) {
+      expect(error.toString()).to.include("ValidatorInactive");
+    }
+    
+    const stakingTokenAccount = await getAssociatedTokenAddress(stakingMint, validator2.publicKey);
+    await mintTo(provider.connection, validator1, stakingMint, stakingTokenAccount, validator1, 100);
+    const unjailAccounts = {
+      authority: validator2.publicKey,
+      validatorConfig: validatorConfigPda,
+      validator: validatorPda,
+      stakingTokenAccount,
+      treasury: treasuryPda,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    
+    // Unjailing waits for the penalty period
+    try {
+      await program.methods
+        .unjail()
+        .accounts(unjailAccounts)
+        .signers([validator2])
+        .rpc();
+      expect.fail("Unjailing during the penalty period should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("JailPeriodNotOver");
+    }
+    
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    const treasuryBefore = await getAccount(provider.connection, treasuryPda);
+    await program.methods
+      .unjail()
+      .accounts(unjailAccounts)
+      .signers([validator2])
+      .rpc();
+    
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.isActive).to.be.true;
+    expect(validatorAccount.jailedUntil.toNumber()).to.equal(0);
+    validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.validatorCount.toNumber()).to.equal(countBefore.toNumber());
+    const treasury = await getAccount(provider.connection, treasuryPda);
+    expect(Number(treasury.amount) - Number(treasuryBefore.amount)).to.equal(100);
+    
+    // Put the defaults back for later suites
+    await program.methods
+      .updateJailConfig(new BN(7 * 24 * 60 * 60), new BN(24 * 60 * 60), new BN(0))
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+  });
+
+  it("Brings back a jailed validator that unstaked in the meantime", async () => {
+    // A fresh validator, so no earlier votes hold its stake
+    const leaver = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(leaver.publicKey, 2 * LAMPORTS_PER_SOL)
+    );
+    const stakingTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      validator1,
+      stakingMint,
+      leaver.publicKey
+    );
+    await mintTo(provider.connection, validator1, stakingMint, stakingTokenAccount, validator1, 2000);
+    const validatorPda = await findValidator(leaver.publicKey);
+    const stakeVault = await findStakeVault(validatorPda);
+    await program.methods
+      .registerValidator(new BN(2000))
+      .accounts({
+        authority: leaver.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        stakingTokenAccount,
+        stakingTokenMint: stakingMint,
+        stakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([leaver])
+      .rpc();
+    
+    // It never votes, so a one-second window soon jails it
+    await program.methods
+      .updateJailConfig(new BN(1), new BN(5), new BN(100))
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    await program.methods
+      .jailValidator()
+      .accounts({
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+      })
+      .rpc();
+    
+    // Reactivating is no way around the jail period
+    const reactivate = () =>
+      program.methods
+        .reactivateValidator(new BN(2000))
+        .accounts({
+          authority: leaver.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorPda,
+          stakingTokenAccount,
+          stakeVault,
+          treasury: treasuryPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([leaver])
+        .rpc();
+    try {
+      await reactivate();
+      expect.fail("Reactivating during the jail period should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("JailPeriodNotOver");
+    }
+    
+    // While jailed it unstakes and takes its stake out
+    await program.methods
+      .requestUnstake()
+      .accounts({
+        authority: leaver.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+      })
+      .signers([leaver])
+      .rpc();
+    let validatorAccount = await program.account.validator.fetch(validatorPda);
+    while ((await provider.connection.getSlot()) < validatorAccount.unbondingUntil.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await program.methods
+      .withdrawStake()
+      .accounts({
+        authority: leaver.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        stakingTokenAccount,
+        stakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([leaver])
+      .rpc();
+    
+    // With no stake left it can't unjail
+    await mintTo(provider.connection, validator1, stakingMint, stakingTokenAccount, validator1, 100);
+    try {
+      await program.methods
+        .unjail()
+        .accounts({
+          authority: leaver.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorPda,
+          stakingTokenAccount,
+          treasury: treasuryPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([leaver])
+        .rpc();
+      expect.fail("Unjailing without stake should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientStake");
+    }
+    
+    // Once the jail period is over, reactivating restakes, pays the unjail
+    // fee and clears the jail
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    const connection = provider.connection;
+    while ((await connection.getBlockTime(await connection.getSlot())) < validatorAccount.jailedUntil.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    
+    await mintTo(provider.connection, validator1, stakingMint, stakingTokenAccount, validator1, 2000);
+    const treasuryBefore = await getAccount(provider.connection, treasuryPda);
+    const countBefore = (await program.account.validatorConfig.fetch(validatorConfigPda)).validatorCount;
+    await reactivate();
+    
+    validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.isActive).to.be.true;
+    expect(validatorAccount.jailedUntil.toNumber()).to.equal(0);
+    expect(validatorAccount.stakeAmount.toNumber()).to.equal(2000);
+    const treasury = await getAccount(provider.connection, treasuryPda);
+    expect(Number(treasury.amount) - Number(treasuryBefore.amount)).to.equal(100);
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    expect(validatorConfig.validatorCount.toNumber()).to.equal(countBefore.toNumber() + 1);
+    
+    // Put the defaults back for later suites
+    await program.methods
+      .updateJailConfig(new BN(7 * 24 * 60 * 60), new BN(24 * 60 * 60), new BN(0))
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+  });
+
+  it("Lets the authority withdraw fees from the treasury", async () => {
+    const destination = await createAssociatedTokenAccount(
+      provider.connection,
+      validator1,
+      stakingMint,
+      wallet.publicKey
+    );
+    const withdrawAccounts = {
+      authority: wallet.publicKey,
+      validatorConfig: validatorConfigPda,
+      treasury: treasuryPda,
+      destination,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    
+    // Only the authority can move treasury funds
+    try {
+      await program.methods
+        .withdrawTreasury(new BN(100))
+        .accounts({ ...withdrawAccounts, authority: validator1.publicKey })
+        .signers([validator1])
+        .rpc();
+      expect.fail("Withdrawing as another wallet should have failed");
+    } catch (error) {
+      expect(error.toString()).to.include("NotAuthorized");
+    }
+    
+    // The unjail fee paid earlier is free to withdraw
+    const treasuryBefore = await getAccount(provider.connection, treasuryPda);
+    await program.methods
+      .withdrawTreasury(new BN(100))
+      .accounts(withdrawAccounts)
+      .rpc();
+    
+    const treasury = await getAccount(provider.connection, treasuryPda);
+    expect(Number(treasuryBefore.amount) - Number(treasury.amount)).to.equal(100);
+    const received = await getAccount(provider.connection, destination);
+    expect(Number(received.amount)).to.equal(100);
+  });
+});
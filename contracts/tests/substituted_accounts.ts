@@ -0,0 +1,524 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { MemoryValidator } from '../target/types/memory_validator';
+import { MemoryNft } from '../target/types/memory_nft';
+import { NramToken } from '../target/types/nram_token';
+import { expect } from 'chai';
+import { createHash } from 'crypto';
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from '@solana/web3.js';
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddress,
+  mintTo,
+  createAssociatedTokenAccount,
+} from '@solana/spl-token';
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+// Passes real accounts in the wrong places and checks that each swap is
+// refused with a specific error. Runs after the memory_validator suite,
+// which initializes the validator config and the NRAM reward mint.
+describe('substituted accounts', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  
+  const program = anchor.workspace.MemoryValidator as Program<MemoryValidator>;
+  const memoryNft = anchor.workspace.MemoryNft as Program<MemoryNft>;
+  const nramToken = anchor.workspace.NramToken as Program<NramToken>;
+  const wallet = provider.wallet;
+  const payer = (wallet as anchor.Wallet).payer;
+  
+  let validatorConfigPda: PublicKey;
+  let stakingMint: PublicKey;
+  let rewardMint: PublicKey;
+  let tokenConfigPda: PublicKey;
+  let memoryDataPda: PublicKey;
+  let memoryId: PublicKey;
+  let otherMemoryId: PublicKey;
+  
+  const validatorA = Keypair.generate();
+  const validatorB = Keypair.generate();
+  const memoryOwner = Keypair.generate();
+  const outsider = Keypair.generate();
+  
+  const findValidator = async (authority: PublicKey) => {
+    const [validator] = await PublicKey.findProgramAddress(
+      [Buffer.from("validator"), authority.toBuffer()],
+      program.programId
+    );
+    return validator;
+  };
+  
+  const findValidation = async (memoryId: PublicKey, validator: PublicKey) => {
+    const [validation] = await PublicKey.findProgramAddress(
+      [Buffer.from("validation"), memoryId.toBuffer(), validator.toBuffer()],
+      program.programId
+    );
+    return validation;
+  };
+  
+  const findStakeVault = async (validator: PublicKey) => {
+    const [vault] = await PublicKey.findProgramAddress(
+      [Buffer.from("stake-vault"), validator.toBuffer()],
+      program.programId
+    );
+    return vault;
+  };
+  
+  const findValidationRound = async (memoryId: PublicKey) => {
+    const [round] = await PublicKey.findProgramAddress(
+      [Buffer.from("validation-round"), memoryId.toBuffer()],
+      program.programId
+    );
+    return round;
+  };
+  
+  const findMetadataAddresses = async (mint: PublicKey) => {
+    const [metadata] = await PublicKey.findProgramAddress(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const [masterEdition] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    return { metadata, masterEdition };
+  };
+  
+  const mintMemory = async (owner: Keypair, neuralData: string) => {
+    const mint = Keypair.generate();
+    const [memory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory"), mint.publicKey.toBuffer()],
+      memoryNft.programId
+    );
+    const fingerprint = [...createHash("sha256").update(neuralData).digest()];
+    const [fingerprintRecord] = await PublicKey.findProgramAddress(
+      [Buffer.from("fingerprint"), Buffer.from(fingerprint)],
+      memoryNft.programId
+    );
+    const { metadata, masterEdition } = await findMetadataAddresses(mint.publicKey);
+    
+    await memoryNft.methods
+      .mintMemory(
+        "https://arweave.net/substituted-memory",
+        "Substituted Memory",
+        "SMEM",
+        { cultural: {} },
+        1,
+        fingerprint,
+        new BN(Math.floor(Date.now() / 1000)),
+        false,
+        0,
+        []
+      )
+      .accounts({
+        authority: owner.publicKey,
+        memoryData: memoryDataPda,
+        memory,
+        fingerprintRecord,
+        mint: mint.publicKey,
+        tokenAccount: await getAssociatedTokenAddress(mint.publicKey, owner.publicKey),
+        metadata,
+        masterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner, mint])
+      .rpc();
+    
+    return { memory, mint: mint.publicKey, fingerprintRecord };
+  };
+  
+  // The token account holding a memory NFT, which is its only account with a balance
+  const findHolderTokenAccount = async (memoryId: PublicKey) => {
+    const memory = await memoryNft.account.memory.fetch(memoryId);
+    const { value } = await provider.connection.getTokenLargestAccounts(memory.mint);
+    return value[0].address;
+  };
+  
+  // Commit and reveal the same vote
+  const vote = async (signer: Keypair, memoryId: PublicKey, qualityScore: number) => {
+    const validatorPda = await findValidator(signer.publicKey);
+    const validation = await findValidation(memoryId, validatorPda);
+    const validationRound = await findValidationRound(memoryId);
+    const salt = Keypair.generate().publicKey.toBuffer();
+    const commitment = [
+      ...createHash("sha256")
+        .update(validatorPda.toBuffer())
+        .update(memoryId.toBuffer())
+        .update(Buffer.from([1, qualityScore]))
+        .update(salt)
+        .digest(),
+    ];
+    
+    await program.methods
+      .commitValidation(commitment)
+      .accounts({
+        authority: signer.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+        memory: memoryId,
+        memoryTokenAccount: await findHolderTokenAccount(memoryId),
+        validation,
+        validationRound,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
+    return async () =>
+      program.methods
+        .revealValidation(true, qualityScore, [...salt])
+        .accounts({
+          authority: signer.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorPda,
+          validation,
+          validationRound,
+        })
+        .signers([signer])
+        .rpc();
+  };
+  
+  // Reveals open once the cluster clock passes the round's commit deadline
+  const waitForRevealPhase = async (memoryId: PublicKey) => {
+    const round = await program.account.validationRound.fetch(await findValidationRound(memoryId));
+    const connection = provider.connection;
+    while ((await connection.getBlockTime(await connection.getSlot())) <= round.commitDeadline.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+  };
+  
+  const expectError = async (call: Promise<unknown>, error: string) => {
+    try {
+      await call;
+      expect.fail(`Expected ${error}`);
+    } catch (err) {
+      expect(err.toString()).to.include(error);
+    }
+  };
+  
+  const processAccounts = async (validatorPda: PublicKey, validation: PublicKey) => ({
+    cranker: wallet.publicKey,
+    validatorConfig: validatorConfigPda,
+    validator: validatorPda,
+    validation,
+    validationRound: await findValidationRound(memoryId),
+    crankerTokenAccount: await getAssociatedTokenAddress(rewardMint, wallet.publicKey),
+    rewardTokenMint: rewardMint,
+    tokenConfig: tokenConfigPda,
+    nramTokenProgram: nramToken.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  });
+  
+  before(async () => {
+    [validatorConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("validator-config")],
+      program.programId
+    );
+    [memoryDataPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory-data")],
+      memoryNft.programId
+    );
+    const validatorConfig = await program.account.validatorConfig.fetch(validatorConfigPda);
+    stakingMint = validatorConfig.stakingTokenMint;
+    rewardMint = validatorConfig.rewardTokenMint;
+    [tokenConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("token-config"), rewardMint.toBuffer()],
+      nramToken.programId
+    );
+    
+    // Two validators are enough to finalize a round here
+    await program.methods
+      .updateValidatorConfig(null, null, 2, null, new BN(5), new BN(3600), null)
+      .accounts({
+        authority: wallet.publicKey,
+        validatorConfig: validatorConfigPda,
+      })
+      .rpc();
+    
+    for (const signer of [validatorA, validatorB, memoryOwner, outsider]) {
+      const airdropSig = await provider.connection.requestAirdrop(
+        signer.publicKey,
+        2 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+    }
+    
+    for (const signer of [validatorA, validatorB]) {
+      const stakingTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        payer,
+        stakingMint,
+        signer.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        payer,
+        stakingMint,
+        stakingTokenAccount,
+        payer,
+        validatorConfig.minStakeAmount.toNumber()
+      );
+      const validatorPda = await findValidator(signer.publicKey);
+      await program.methods
+        .registerValidator(validatorConfig.minStakeAmount)
+        .accounts({
+          authority: signer.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorPda,
+          stakingTokenAccount,
+          stakingTokenMint: stakingMint,
+          stakeVault: await findStakeVault(validatorPda),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([signer])
+        .rpc();
+    }
+    
+    // Both validators vote on one memory, which finalizes its round
+    memoryId = (await mintMemory(memoryOwner, "substituted-accounts-memory")).memory;
+    otherMemoryId = (await mintMemory(memoryOwner, "substituted-accounts-other-memory")).memory;
+    const revealA = await vote(validatorA, memoryId, 6);
+    const revealB = await vote(validatorB, memoryId, 8);
+    await waitForRevealPhase(memoryId);
+    await revealA();
+    await revealB();
+  });
+
+  it("Rejects a token config that belongs to another mint", async () => {
+    await expectError(
+      nramToken.methods
+        .mintTokens(new BN(1))
+        .accounts({
+          authority: wallet.publicKey,
+          tokenConfig: tokenConfigPda,
+          mint: stakingMint,
+          tokenAccount: await getAssociatedTokenAddress(stakingMint, validatorA.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      "InvalidMint"
+    );
+  });
+
+  it("Rejects burning from a token account the signer does not own", async () => {
+    await expectError(
+      nramToken.methods
+        .burnTokens(new BN(1))
+        .accounts({
+          authority: outsider.publicKey,
+          tokenConfig: tokenConfigPda,
+          mint: rewardMint,
+          tokenAccount: await getAssociatedTokenAddress(rewardMint, wallet.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([outsider])
+        .rpc(),
+      "InvalidTokenAccount"
+    );
+  });
+
+  it("Rejects a reward minter change from anyone but the authority", async () => {
+    await expectError(
+      nramToken.methods
+        .setRewardMinter(outsider.publicKey)
+        .accounts({
+          authority: outsider.publicKey,
+          tokenConfig: tokenConfigPda,
+          mint: rewardMint,
+        })
+        .signers([outsider])
+        .rpc(),
+      "NotAuthorized"
+    );
+  });
+
+  it("Rejects processing one validator's vote against another validator", async () => {
+    const validatorAPda = await findValidator(validatorA.publicKey);
+    const validatorBPda = await findValidator(validatorB.publicKey);
+    const validation = await findValidation(memoryId, validatorAPda);
+    
+    await expectError(
+      program.methods
+        .processValidation()
+        .accounts(await processAccounts(validatorBPda, validation))
+        .rpc(),
+      "ValidatorMismatch"
+    );
+  });
+
+  it("Rejects revealing through another validator's account", async () => {
+    const validatorAPda = await findValidator(validatorA.publicKey);
+    
+    await expectError(
+      program.methods
+        .revealValidation(true, 6, [...Buffer.alloc(32)])
+        .accounts({
+          authority: validatorB.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorAPda,
+          validation: await findValidation(memoryId, validatorAPda),
+          validationRound: await findValidationRound(memoryId),
+        })
+        .signers([validatorB])
+        .rpc(),
+      "NotAuthorized"
+    );
+  });
+
+  it("Rejects a crank tip account for the wrong mint", async () => {
+    const validatorPda = await findValidator(validatorA.publicKey);
+    const accounts = await processAccounts(
+      validatorPda,
+      await findValidation(memoryId, validatorPda)
+    );
+    accounts.crankerTokenAccount = await getAssociatedTokenAddress(
+      stakingMint,
+      validatorA.publicKey
+    );
+    
+    await expectError(
+      program.methods.processValidation().accounts(accounts).rpc(),
+      "InvalidTokenAccount"
+    );
+  });
+
+  it("Rejects finalizing a memory's quality with another memory's round", async () => {
+    await expectError(
+      program.methods
+        .finalizeMemoryQuality()
+        .accounts({
+          validatorConfig: validatorConfigPda,
+          validationRound: await findValidationRound(memoryId),
+          memory: otherMemoryId,
+          memoryNftProgram: memoryNft.programId,
+        })
+        .rpc(),
+      "MemoryMismatch"
+    );
+  });
+
+  it("Rejects claiming when no rewards are owed", async () => {
+    await expectError(
+      program.methods
+        .claimRewards()
+        .accounts({
+          authority: validatorB.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: await findValidator(validatorB.publicKey),
+          rewardTokenAccount: await createAssociatedTokenAccount(
+            provider.connection,
+            validatorB,
+            rewardMint,
+            validatorB.publicKey
+          ),
+          rewardTokenMint: rewardMint,
+          tokenConfig: tokenConfigPda,
+          nramTokenProgram: nramToken.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([validatorB])
+        .rpc(),
+      "NoRewardsToClaim"
+    );
+  });
+
+  it("Rejects withdrawing stake into someone else's token account", async () => {
+    const validatorPda = await findValidator(validatorB.publicKey);
+    
+    await expectError(
+      program.methods
+        .withdrawStake()
+        .accounts({
+          authority: validatorB.publicKey,
+          validatorConfig: validatorConfigPda,
+          validator: validatorPda,
+          stakingTokenAccount: await getAssociatedTokenAddress(stakingMint, validatorA.publicKey),
+          stakeVault: await findStakeVault(validatorPda),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([validatorB])
+        .rpc(),
+      "InvalidTokenAccount"
+    );
+  });
+
+  it("Still processes a vote for a validator that has left", async () => {
+    const validatorPda = await findValidator(validatorA.publicKey);
+    await program.methods
+      .requestUnstake()
+      .accounts({
+        authority: validatorA.publicKey,
+        validatorConfig: validatorConfigPda,
+        validator: validatorPda,
+      })
+      .signers([validatorA])
+      .rpc();
+    
+    // Unbonding doesn't let a validator dodge the verdict on votes it cast
+    const validation = await findValidation(memoryId, validatorPda);
+    await program.methods
+      .processValidation()
+      .accounts(await processAccounts(validatorPda, validation))
+      .rpc();
+    
+    const validationAccount = await program.account.validation.fetch(validation);
+    expect(validationAccount.isProcessed).to.be.true;
+    const validatorAccount = await program.account.validator.fetch(validatorPda);
+    expect(validatorAccount.isActive).to.be.false;
+    expect(validatorAccount.rewardsEarned.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Rejects burning a memory with another memory's fingerprint record", async () => {
+    const target = await mintMemory(memoryOwner, "substituted-burn-target");
+    const other = await mintMemory(memoryOwner, "substituted-burn-other");
+    const { metadata, masterEdition } = await findMetadataAddresses(target.mint);
+    const [memoryHistory] = await PublicKey.findProgramAddress(
+      [Buffer.from("memory-history"), target.memory.toBuffer()],
+      memoryNft.programId
+    );
+    
+    await expectError(
+      memoryNft.methods
+        .burnMemory()
+        .accounts({
+          owner: memoryOwner.publicKey,
+          memoryData: memoryDataPda,
+          memory: target.memory,
+          fingerprintRecord: other.fingerprintRecord,
+          memoryHistory,
+          mint: target.mint,
+          tokenAccount: await getAssociatedTokenAddress(target.mint, memoryOwner.publicKey),
+          metadata,
+          masterEdition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([memoryOwner])
+        .rpc(),
+      "InvalidFingerprintRecord"
+    );
+  });
+});